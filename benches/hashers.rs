@@ -0,0 +1,55 @@
+// Insert and lookup throughput of a HashMap<String, usize> with the default SipHash hasher,
+// FNV and (with the ahash feature) aHash, the comparison of the hashers section of
+// 8-collections.rs. build_map and lookup_all come from the collections module of the library.
+//
+// Run with: cargo bench --bench hashers
+//      or: cargo bench --features ahash --bench hashers
+
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput};
+use esercizi_malnati::collections::{build_map, lookup_all};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::hint::black_box;
+
+const KEYS: usize = 100_000;
+
+fn keys() -> Vec<String> {
+    (0..KEYS).map(|i| format!("key-{i}")).collect()
+}
+
+fn bench_hasher<S: BuildHasher + Default>(
+    group: &mut BenchmarkGroup<'_, criterion::measurement::WallTime>,
+    name: &str,
+    keys: &[String],
+    probes: &[String],
+) {
+    group.bench_function(format!("{name}/insert"), |b| {
+        b.iter(|| black_box(build_map::<S>(keys)))
+    });
+    let map = build_map::<S>(keys);
+    group.bench_function(format!("{name}/lookup"), |b| {
+        b.iter(|| black_box(lookup_all(&map, probes)))
+    });
+}
+
+fn hashers(c: &mut Criterion) {
+    let keys = keys();
+    // Half of the probes are present keys and half are missing, KEYS lookups in total.
+    let probes: Vec<String> = keys[..KEYS / 2]
+        .iter()
+        .cloned()
+        .chain((KEYS..KEYS + KEYS / 2).map(|i| format!("key-{i}")))
+        .collect();
+
+    let mut group = c.benchmark_group("hashmap_hashers");
+    group.throughput(Throughput::Elements(KEYS as u64));
+    group.sample_size(20);
+    bench_hasher::<RandomState>(&mut group, "siphash", &keys, &probes);
+    bench_hasher::<fnv::FnvBuildHasher>(&mut group, "fnv", &keys, &probes);
+    #[cfg(feature = "ahash")]
+    bench_hasher::<ahash::RandomState>(&mut group, "ahash", &keys, &probes);
+    group.finish();
+}
+
+criterion_group!(benches, hashers);
+criterion_main!(benches);
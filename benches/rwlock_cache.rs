@@ -0,0 +1,29 @@
+// Throughput of the read-heavy cache of 12-concurrency.rs with std::sync::RwLock and
+// parking_lot::RwLock, both from the rwlock_cache module of the library.
+//
+// Run with: cargo bench --features parking_lot --bench rwlock_cache
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use esercizi_malnati::rwlock_cache::{prefilled, read_heavy_workload, ParkingLotCache, StdCache};
+use std::hint::black_box;
+
+const THREADS: u64 = 4;
+const OPS: u64 = 10_000;
+
+fn rwlock_cache(c: &mut Criterion) {
+    let std_cache = StdCache(std::sync::RwLock::new(prefilled()));
+    let parking_cache = ParkingLotCache(parking_lot::RwLock::new(prefilled()));
+
+    let mut group = c.benchmark_group("read_heavy_cache");
+    group.throughput(Throughput::Elements(THREADS * OPS));
+    group.bench_function("std_rwlock", |b| {
+        b.iter(|| black_box(read_heavy_workload(&std_cache, THREADS, OPS)))
+    });
+    group.bench_function("parking_lot_rwlock", |b| {
+        b.iter(|| black_box(read_heavy_workload(&parking_cache, THREADS, OPS)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, rwlock_cache);
+criterion_main!(benches);
@@ -136,3 +136,1079 @@
 //  Proper memory allocation is DIFFICULT!
 //  Often tools as valgrind and Dr.Memory are used to check if the program written by ourself is
 //  referencing memory in a risky way.
+//
+// ----------------- EXAMPLES ------------------
+//
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+// BUMP ALLOCATOR
+// In Rust malloc and free are hidden behind the GlobalAlloc trait: Box, Vec, String and every
+// other heap allocation call alloc and dealloc of the allocator marked #[global_allocator], and
+// any allocator can also be called directly through the trait, as the example does.
+// A bump allocator is the simplest possible one: it owns a fixed region of bytes and an offset,
+// each allocation rounds the offset up to the alignment requested by the Layout and moves it
+// forward by the size. dealloc does nothing, the memory is reused only when the whole region
+// is thrown away, and when the region is exhausted alloc returns null (Rust then aborts).
+// The offset is an AtomicUsize because a GlobalAlloc is shared by all threads.
+// A zero sized allocation doesn't need memory but must return a non null pointer, aligned as
+// requested: the address equal to the alignment is the usual dangling pointer.
+struct BumpAllocator<const N: usize> {
+    region: UnsafeCell<[u8; N]>,
+    offset: AtomicUsize,
+}
+
+// The region is only handed out in disjoint pieces reserved with the atomic offset.
+unsafe impl<const N: usize> Sync for BumpAllocator<N> {}
+
+impl<const N: usize> BumpAllocator<N> {
+    const fn new() -> Self {
+        BumpAllocator {
+            region: UnsafeCell::new([0; N]),
+            offset: AtomicUsize::new(0),
+        }
+    }
+
+    fn used(&self) -> usize {
+        self.offset.load(Ordering::SeqCst)
+    }
+}
+
+unsafe impl<const N: usize> GlobalAlloc for BumpAllocator<N> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.size() == 0 {
+            return ptr::without_provenance_mut(layout.align());
+        }
+        let base = self.region.get() as *mut u8;
+        let mut start = 0;
+        let reserved = self
+            .offset
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |offset| {
+                // Align the address, not the offset: the region itself is only 1-aligned.
+                let address = (base as usize).checked_add(offset)?;
+                let aligned = address.checked_next_multiple_of(layout.align())? - base as usize;
+                let end = aligned.checked_add(layout.size())?;
+                start = aligned;
+                (end <= N).then_some(end)
+            });
+        match reserved {
+            Ok(_) => unsafe { base.add(start) },
+            Err(_) => ptr::null_mut(),
+        }
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+}
+
+fn bump_allocator_example() {
+    let small = BumpAllocator::<64>::new();
+    unsafe {
+        let a = small.alloc(Layout::from_size_align(3, 1).unwrap());
+        let b = small.alloc(Layout::from_size_align(8, 8).unwrap());
+        let full = small.alloc(Layout::from_size_align(64, 1).unwrap());
+        println!(
+            "bump allocator: 3 bytes at {a:p}, 8 aligned bytes at {b:p}, 64 more bytes null {}",
+            full.is_null()
+        );
+    }
+    println!("bump allocator: {} bytes used", small.used());
+}
+
+// TYPED ARENA
+// An arena allocates many values of the same type and frees them all together when it is
+// dropped, so the references it hands out live as long as the arena: &self -> &T.
+// alloc takes &self (so several references can coexist) and the chunks are in a RefCell.
+// A single growing Vec<T> wouldn't work: when it is full push reallocates the buffer and moves
+// every element, leaving the references already returned dangling. With Vec<Vec<T>> a chunk is
+// never pushed beyond its capacity, a new chunk is created instead, so the elements never
+// move; only the outer Vec is reallocated, and it moves the chunk headers, not their buffers.
+struct Arena<T> {
+    chunks: RefCell<Vec<Vec<T>>>,
+    chunk_size: usize,
+}
+
+impl<T> Arena<T> {
+    fn new(chunk_size: usize) -> Self {
+        Arena {
+            chunks: RefCell::new(Vec::new()),
+            chunk_size: chunk_size.max(1),
+        }
+    }
+
+    fn alloc(&self, value: T) -> &T {
+        let mut chunks = self.chunks.borrow_mut();
+        if chunks
+            .last()
+            .is_none_or(|chunk| chunk.len() == chunk.capacity())
+        {
+            chunks.push(Vec::with_capacity(self.chunk_size));
+        }
+        let chunk = chunks.last_mut().unwrap();
+        chunk.push(value);
+        let slot: *const T = chunk.last().unwrap();
+        // The chunk is never grown past its capacity nor dropped before the arena, so the
+        // element stays at this address for the whole lifetime of &self.
+        unsafe { &*slot }
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.chunks.borrow().len()
+    }
+}
+
+fn arena_example() {
+    let names = Arena::new(1);
+    let first = names.alloc(String::from("first"));
+    let second = names.alloc(String::from("second"));
+    println!(
+        "arena: {first} and {second} in {} chunks",
+        names.chunk_count()
+    );
+}
+
+// POOL ALLOCATOR
+// When all the allocations have the same size the heap can be replaced by a pool: a buffer
+// carved into equally sized blocks. The free blocks form a linked list whose links are stored
+// inside the free blocks themselves (an intrusive free list), so the pool needs no memory
+// other than the buffer: allocate pops the head of the list, deallocate pushes the block back.
+// Both are O(1) and a freed block is the first to be reused.
+// A flag per block detects a double free, which in C would silently corrupt the list.
+const NO_BLOCK: usize = usize::MAX;
+
+#[derive(Debug, PartialEq)]
+enum PoolError {
+    DoubleFree(usize),
+    ForeignPointer,
+}
+
+struct PoolAllocator {
+    base: *mut u8,
+    block_size: usize,
+    blocks: usize,
+    free_head: usize,
+    allocated: Vec<bool>,
+}
+
+impl PoolAllocator {
+    fn new(block_size: usize, blocks: usize) -> Self {
+        // Each free block must be able to hold the index of the next one.
+        let block_size = block_size.max(size_of::<usize>());
+        let buffer = vec![0u8; block_size * blocks].into_boxed_slice();
+        let base = Box::into_raw(buffer) as *mut u8;
+        let mut pool = PoolAllocator {
+            base,
+            block_size,
+            blocks,
+            free_head: NO_BLOCK,
+            allocated: vec![false; blocks],
+        };
+        for index in (0..blocks).rev() {
+            pool.push_free(index);
+        }
+        pool
+    }
+
+    fn block(&self, index: usize) -> *mut u8 {
+        unsafe { self.base.add(index * self.block_size) }
+    }
+
+    fn push_free(&mut self, index: usize) {
+        unsafe { (self.block(index) as *mut usize).write_unaligned(self.free_head) };
+        self.free_head = index;
+    }
+
+    fn allocate(&mut self) -> Option<*mut u8> {
+        if self.free_head == NO_BLOCK {
+            return None;
+        }
+        let index = self.free_head;
+        self.free_head = unsafe { (self.block(index) as *const usize).read_unaligned() };
+        self.allocated[index] = true;
+        Some(self.block(index))
+    }
+
+    fn deallocate(&mut self, ptr: *mut u8) -> Result<(), PoolError> {
+        let offset = (ptr as usize).wrapping_sub(self.base as usize);
+        if offset >= self.block_size * self.blocks || !offset.is_multiple_of(self.block_size) {
+            return Err(PoolError::ForeignPointer);
+        }
+        let index = offset / self.block_size;
+        if !self.allocated[index] {
+            return Err(PoolError::DoubleFree(index));
+        }
+        self.allocated[index] = false;
+        self.push_free(index);
+        Ok(())
+    }
+}
+
+impl Drop for PoolAllocator {
+    fn drop(&mut self) {
+        let slice = ptr::slice_from_raw_parts_mut(self.base, self.block_size * self.blocks);
+        drop(unsafe { Box::from_raw(slice) });
+    }
+}
+
+fn pool_allocator_example() {
+    let mut pool = PoolAllocator::new(32, 2);
+    let first = pool.allocate().unwrap();
+    let second = pool.allocate().unwrap();
+    println!(
+        "pool allocator: blocks {first:p} and {second:p}, a third one {:?}",
+        pool.allocate()
+    );
+    pool.deallocate(first).unwrap();
+    println!(
+        "pool allocator: the freed block is reused {}",
+        pool.allocate() == Some(first)
+    );
+}
+
+// STACK FRAMES
+// The f(2) example from the top of the file, compiled as it is written there. trace_frame
+// prints the address of one of its locals, which lives in the frame of trace_frame itself,
+// pushed on top of the frame of the caller. Calling it from main and from traced_f, a wrapper
+// that calls f one level deeper, shows that the deeper call has a lower address: the stack
+// grows to the bottom.
+// #[inline(never)] keeps the functions as separate frames even when optimized, and the
+// address is passed through black_box so the local is really stored in memory.
+// The direction is a property of the architecture: on the common ones (x86, ARM, RISC-V) the
+// stack grows downward, on the others the example just prints the addresses.
+#[inline(never)]
+fn trace_frame(name: &str) -> usize {
+    let local = 0u8;
+    let address = std::hint::black_box(&local) as *const u8 as usize;
+    println!("  frame of {name}: local at {address:#x}");
+    address
+}
+
+#[inline(never)]
+fn f(a: i32) -> i32 {
+    let b = a + 1;
+    b * b
+}
+
+// Runs f(a) from a frame of its own, one level below the caller, and returns the result with
+// the address traced in that frame.
+#[inline(never)]
+fn traced_f(a: i32) -> (i32, usize) {
+    let frame = trace_frame("traced_f");
+    (f(a), frame)
+}
+
+fn stack_frames_example() {
+    let main_frame = trace_frame("main");
+    // black_box hides the constant argument, otherwise the optimizer computes 9 in advance
+    // and turns the call of trace_frame into a jump that reuses the frame of traced_f.
+    let (x, f_frame) = traced_f(std::hint::black_box(2));
+    println!(
+        "stack frames: f(2) = {x}, nested frame {} bytes lower",
+        main_frame as isize - f_frame as isize
+    );
+}
+
+// COUNTING ALLOCATOR
+// GlobalAlloc implementations can be stacked: CountingAllocator forwards every call to the
+// allocator it wraps and adds Layout::size() to the allocated or freed counters. The memory
+// still in use has a counter of its own, moved by the same calls: subtracting the other two
+// would read them at different moments, and a free landing between the two loads could even
+// make the difference underflow. It makes the claims about leakage measurable: a value
+// dropped gives its bytes back, a value leaked (mem::forget, Box::leak, Rc cycles) doesn't.
+// The global allocator of this file is a CountingAllocator around std::alloc::System, so it
+// counts every allocation of the normal heap made by the program.
+// realloc is forwarded too, so the wrapped allocator can grow a block in place.
+struct CountingAllocator<A> {
+    inner: A,
+    allocated: AtomicUsize,
+    freed: AtomicUsize,
+    live: AtomicUsize,
+}
+
+impl<A> CountingAllocator<A> {
+    const fn new(inner: A) -> Self {
+        CountingAllocator {
+            inner,
+            allocated: AtomicUsize::new(0),
+            freed: AtomicUsize::new(0),
+            live: AtomicUsize::new(0),
+        }
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.allocated.load(Ordering::SeqCst)
+    }
+
+    fn freed_bytes(&self) -> usize {
+        self.freed.load(Ordering::SeqCst)
+    }
+
+    fn live_bytes(&self) -> usize {
+        self.live.load(Ordering::SeqCst)
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+        if !ptr.is_null() {
+            self.allocated.fetch_add(layout.size(), Ordering::SeqCst);
+            self.live.fetch_add(layout.size(), Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) };
+        self.freed.fetch_add(layout.size(), Ordering::SeqCst);
+        self.live.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            self.freed.fetch_add(layout.size(), Ordering::SeqCst);
+            self.allocated.fetch_add(new_size, Ordering::SeqCst);
+            if new_size >= layout.size() {
+                self.live
+                    .fetch_add(new_size - layout.size(), Ordering::SeqCst);
+            } else {
+                self.live
+                    .fetch_sub(layout.size() - new_size, Ordering::SeqCst);
+            }
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator<System> = CountingAllocator::new(System);
+
+fn counting_allocator_example() {
+    let baseline = GLOBAL.live_bytes();
+    let numbers: Vec<u32> = (0..1000).collect();
+    println!(
+        "counting allocator: {} more bytes live with a Vec of 1000 u32",
+        GLOBAL.live_bytes() - baseline
+    );
+    drop(numbers);
+
+    // A leaked Box stays live forever.
+    let leaked: &mut [u8; 64] = Box::leak(Box::new([0; 64]));
+    leaked[0] = 1;
+    println!(
+        "counting allocator: {} more bytes live after dropping the Vec and leaking a Box",
+        GLOBAL.live_bytes() - baseline
+    );
+    println!(
+        "counting allocator: {} bytes allocated by the program so far, {} freed",
+        GLOBAL.allocated_bytes(),
+        GLOBAL.freed_bytes()
+    );
+}
+
+// VEC REALLOCATION
+// A Vec stores its elements in a heap buffer of `capacity` elements: when a push finds it
+// full, a bigger buffer is allocated, the elements are copied and the old one is freed, so the
+// address of the elements changes (unless the allocator manages to grow the block in place,
+// which the system allocator sometimes does).
+// push_history records the buffer address and the capacity after every push, observe_realloc
+// keeps the distinct addresses. It goes up to 100000 bytes: a small buffer often finds free
+// room right after it and grows in place, a large one sooner or later has to move.
+fn push_history(n: usize) -> Vec<(usize, usize)> {
+    let mut v: Vec<u8> = Vec::new();
+    (0..n)
+        .map(|i| {
+            v.push(i as u8);
+            (v.as_ptr() as usize, v.capacity())
+        })
+        .collect()
+}
+
+fn observe_realloc() -> Vec<usize> {
+    let mut pointers: Vec<usize> = Vec::new();
+    for (ptr, _) in push_history(100_000) {
+        if pointers.last() != Some(&ptr) {
+            pointers.push(ptr);
+        }
+    }
+    pointers
+}
+
+fn realloc_example() {
+    println!(
+        "vec reallocation: {} buffers while growing to 100000 bytes",
+        observe_realloc().len()
+    );
+}
+
+// MALLOC OVER A BYTE BUFFER
+// What malloc and free do inside the heap, simulated over a slice of bytes. The slice is split
+// in blocks and each block starts with a header and ends with a footer (boundary tags) holding
+// its size and whether it is used, the addresses returned are offsets in the slice.
+// - alloc walks the blocks from the first one and takes the first free block big enough
+// (first-fit), splitting it when the rest is big enough to be a block on its own.
+// - free marks the block as free and merges it with the next block and, reading the footer
+// just before its header, with the previous one when they are free too (coalescing).
+// Without coalescing, after freeing two neighbours the heap would have two small holes where a
+// bigger allocation could fit.
+const TAG: usize = 8;
+const MIN_BLOCK: usize = 2 * TAG + 8;
+const USED: u64 = 1;
+
+#[derive(Debug, PartialEq)]
+enum HeapError {
+    InvalidOffset(usize),
+    DoubleFree(usize),
+}
+
+struct HeapSim<'a> {
+    heap: &'a mut [u8],
+}
+
+impl<'a> HeapSim<'a> {
+    fn new(buffer: &'a mut [u8]) -> Self {
+        let len = buffer.len() / TAG * TAG;
+        assert!(len >= MIN_BLOCK, "heap of {len} bytes is too small");
+        let mut sim = HeapSim {
+            heap: &mut buffer[..len],
+        };
+        sim.write_block(0, len, false);
+        sim
+    }
+
+    fn read_tag(&self, at: usize) -> (usize, bool) {
+        let tag = u64::from_le_bytes(self.heap[at..at + TAG].try_into().unwrap());
+        ((tag & !USED) as usize, tag & USED == USED)
+    }
+
+    fn write_block(&mut self, start: usize, size: usize, used: bool) {
+        let tag = (size as u64 | if used { USED } else { 0 }).to_le_bytes();
+        self.heap[start..start + TAG].copy_from_slice(&tag);
+        self.heap[start + size - TAG..start + size].copy_from_slice(&tag);
+    }
+
+    fn blocks(&self) -> Vec<(usize, usize, bool)> {
+        let mut blocks = Vec::new();
+        let mut start = 0;
+        while start < self.heap.len() {
+            let (size, used) = self.read_tag(start);
+            blocks.push((start, size, used));
+            start += size;
+        }
+        blocks
+    }
+
+    fn alloc(&mut self, size: usize, align: usize) -> Option<usize> {
+        let align = align.max(TAG).next_power_of_two();
+        let total = size.max(1).next_multiple_of(TAG) + 2 * TAG;
+        for (start, block_size, used) in self.blocks() {
+            if used {
+                continue;
+            }
+            // The bytes skipped to align the payload must be empty or a free block.
+            let mut gap = (start + TAG).next_multiple_of(align) - TAG - start;
+            if gap != 0 && gap < MIN_BLOCK {
+                gap = (start + TAG + MIN_BLOCK).next_multiple_of(align) - TAG - start;
+            }
+            if gap + total > block_size {
+                continue;
+            }
+            if gap > 0 {
+                self.write_block(start, gap, false);
+            }
+            let block = start + gap;
+            let rest = block_size - gap - total;
+            if rest >= MIN_BLOCK {
+                self.write_block(block, total, true);
+                self.write_block(block + total, rest, false);
+            } else {
+                self.write_block(block, total + rest, true);
+            }
+            return Some(block + TAG);
+        }
+        None
+    }
+
+    fn free(&mut self, offset: usize) -> Result<(), HeapError> {
+        let start = offset.wrapping_sub(TAG);
+        let (_, mut size, used) = self
+            .blocks()
+            .into_iter()
+            .find(|&(block, _, _)| block == start)
+            .ok_or(HeapError::InvalidOffset(offset))?;
+        if !used {
+            return Err(HeapError::DoubleFree(offset));
+        }
+        let mut start = start;
+        if start + size < self.heap.len() {
+            let (next_size, next_used) = self.read_tag(start + size);
+            if !next_used {
+                size += next_size;
+            }
+        }
+        if start > 0 {
+            let (prev_size, prev_used) = self.read_tag(start - TAG);
+            if !prev_used {
+                start -= prev_size;
+                size += prev_size;
+            }
+        }
+        self.write_block(start, size, false);
+        Ok(())
+    }
+}
+
+// Four blocks of 40 bytes in `buffer` with the second one freed again, a hole of 56 bytes
+// between two used blocks. Returns the heap and the offsets of the four blocks.
+fn fragmented_heap(buffer: &mut [u8]) -> (HeapSim<'_>, Vec<usize>) {
+    let mut heap = HeapSim::new(buffer);
+    let blocks: Vec<usize> = (0..4).map(|_| heap.alloc(40, 8).unwrap()).collect();
+    heap.free(blocks[1]).unwrap();
+    (heap, blocks)
+}
+
+fn heap_sim_example() {
+    let mut buffer = [0u8; 256];
+    let (mut heap, blocks) = fragmented_heap(&mut buffer);
+    println!(
+        "heap simulation: 90 bytes in a hole of 56 {:?}",
+        heap.alloc(90, 8)
+    );
+
+    // Freeing the neighbour merges the two holes and the big allocation fits.
+    heap.free(blocks[2]).unwrap();
+    println!(
+        "heap simulation: 90 bytes after coalescing {:?}",
+        heap.alloc(90, 8)
+    );
+}
+
+// DANGLING POINTERS
+// use_after_free_guarded keeps the address of a String buffer after the String has been
+// dropped and reads it: undefined behaviour, the bytes may still be there, be overwritten by
+// the next allocation or the read may crash (the system allocator often reuses them right
+// away). The compiler is free to assume it never happens, so it isn't even guaranteed that the
+// read takes place.
+// It is compiled only with the demo_ub feature so the normal run stays sound.
+// In safe Rust the same code doesn't compile: a reference can't outlive the String, the fix
+// is returning the owned String (moving it) as safe_alternative does.
+// Run with: cargo run --bin 0-allocation --features demo_ub
+#[cfg(feature = "demo_ub")]
+fn use_after_free_guarded() -> String {
+    let dangling: *const u8;
+    {
+        let text = String::from("hello");
+        dangling = text.as_ptr();
+    }
+    let _reuse = String::from("XXXXX");
+    let bytes = unsafe { std::slice::from_raw_parts(dangling, 5) };
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn safe_alternative() -> String {
+    let text = String::from("hello");
+    // let r = &text; ... r returned here would be "borrowed value does not live long enough".
+    text
+}
+
+fn dangling_example() {
+    #[cfg(feature = "demo_ub")]
+    println!(
+        "dangling read (undefined behaviour): {:?}",
+        use_after_free_guarded()
+    );
+    println!(
+        "dangling pointers: the owned String is always {:?}",
+        safe_alternative()
+    );
+}
+
+// SIZE AND ALIGNMENT
+// The size of every type is known at compile time, size_of and align_of read it.
+// &str is a fat pointer (address and length), a Vec is pointer, capacity and length.
+// Option<Box<u8>> is as big as a Box: a Box can't be null, so None is represented with the
+// null pointer and no tag is needed (null pointer optimization).
+// In a struct the fields are reordered by the compiler to waste less padding, but the size
+// is still rounded up to a multiple of the alignment.
+struct Sample {
+    _flag: bool,
+    _value: u64,
+    _small: u16,
+}
+
+fn layout_report() -> Vec<(&'static str, usize, usize)> {
+    vec![
+        ("u8", size_of::<u8>(), align_of::<u8>()),
+        ("u64", size_of::<u64>(), align_of::<u64>()),
+        ("char", size_of::<char>(), align_of::<char>()),
+        ("&str", size_of::<&str>(), align_of::<&str>()),
+        ("Vec<u8>", size_of::<Vec<u8>>(), align_of::<Vec<u8>>()),
+        (
+            "Option<Box<u8>>",
+            size_of::<Option<Box<u8>>>(),
+            align_of::<Option<Box<u8>>>(),
+        ),
+        (
+            "Option<u32>",
+            size_of::<Option<u32>>(),
+            align_of::<Option<u32>>(),
+        ),
+        ("Sample", size_of::<Sample>(), align_of::<Sample>()),
+    ]
+}
+
+fn layout_example() {
+    println!("layout:");
+    for (name, size, align) in layout_report() {
+        println!("  {name:<16} size {size:>2} align {align}");
+    }
+}
+
+// RESETTABLE ARENA
+// An arena with a fixed capacity, allocated once, that a loop can empty at every iteration
+// instead of freeing and allocating again: reset drops the values and moves the index back
+// to the first slot, the memory stays, so the next alloc returns the same address as the
+// first one did.
+// alloc takes &self and returns &mut T: every slot is handed out only once, so the mutable
+// references never overlap. reset takes &mut self, and the borrow checker refuses to call it
+// while any of those references is alive, they would point to values that no longer exist.
+struct ResettableArena<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    len: Cell<usize>,
+}
+
+impl<T> ResettableArena<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        ResettableArena {
+            slots: (0..capacity)
+                .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+                .collect(),
+            len: Cell::new(0),
+        }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    fn alloc(&self, value: T) -> Option<&mut T> {
+        let index = self.len.get();
+        let slot = self.slots.get(index)?;
+        self.len.set(index + 1);
+        Some(unsafe { (*slot.get()).write(value) })
+    }
+
+    fn reset(&mut self) {
+        for slot in &mut self.slots[..self.len.get()] {
+            unsafe { slot.get_mut().assume_init_drop() };
+        }
+        self.len.set(0);
+    }
+}
+
+impl<T> Drop for ResettableArena<T> {
+    fn drop(&mut self) {
+        self.reset();
+    }
+}
+
+fn resettable_arena_example() {
+    let mut arena = ResettableArena::with_capacity(4);
+    for round in 0..3 {
+        let text = arena.alloc(format!("round {round}")).unwrap();
+        println!("resettable arena: {text} at {:p}", text);
+        // let stale = &*text; arena.reset(); stale.len() -> error[E0502]
+        arena.reset();
+    }
+}
+
+// STACK DEPTH GUARD
+// A recursion that goes too deep exhausts the stack and the program is killed (stack
+// overflow). RecursionGuard remembers the address of a local of the function that starts the
+// recursion: every recursive call measures the distance between that address and one of its
+// own locals, which is roughly the stack used since then, and stops with an error when it
+// exceeds the limit instead of crashing. abs_diff makes it work whatever the direction in
+// which the stack grows. The limit is an argument of check_depth, not part of the guard, so
+// fib_guarded passes it down together with the guard.
+#[derive(Debug, PartialEq)]
+struct StackExhausted {
+    used: usize,
+    limit: usize,
+}
+
+struct RecursionGuard {
+    base: usize,
+}
+
+impl RecursionGuard {
+    #[inline(always)]
+    fn new() -> Self {
+        let local = 0u8;
+        RecursionGuard {
+            base: std::hint::black_box(&local) as *const u8 as usize,
+        }
+    }
+
+    #[inline(always)]
+    fn check_depth(&self, limit_bytes: usize) -> Result<(), StackExhausted> {
+        let local = 0u8;
+        let here = std::hint::black_box(&local) as *const u8 as usize;
+        let used = self.base.abs_diff(here);
+        if used > limit_bytes {
+            Err(StackExhausted {
+                used,
+                limit: limit_bytes,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn fib_guarded(n: u64, guard: &RecursionGuard, limit_bytes: usize) -> Result<u64, StackExhausted> {
+    guard.check_depth(limit_bytes)?;
+    if n < 2 {
+        return Ok(n);
+    }
+    Ok(fib_guarded(n - 1, guard, limit_bytes)? + fib_guarded(n - 2, guard, limit_bytes)?)
+}
+
+fn recursion_guard_example() {
+    // 10000 nested calls would need much more than 4 KiB of stack.
+    let guard = RecursionGuard::new();
+    let error = fib_guarded(10_000, &guard, 4096).unwrap_err();
+    println!(
+        "recursion guard: stopped after {} bytes of stack",
+        error.used
+    );
+}
+
+// STACK OR HEAP
+// Filling an array on the stack costs only the writes: its space is reserved by moving the
+// stack pointer when the function is entered. A Vec of the same length also pays an
+// allocation and a deallocation at every iteration.
+// black_box stops the optimizer from removing the buffers since their content is never used.
+fn bench_stack_vs_heap(iters: usize) -> (Duration, Duration) {
+    let start = Instant::now();
+    for i in 0..iters {
+        let mut buffer = [0u64; 1024];
+        for (j, slot) in buffer.iter_mut().enumerate() {
+            *slot = (i + j) as u64;
+        }
+        std::hint::black_box(&buffer);
+    }
+    let stack = start.elapsed();
+
+    let start = Instant::now();
+    for i in 0..iters {
+        let mut buffer = vec![0u64; 1024];
+        for (j, slot) in buffer.iter_mut().enumerate() {
+            *slot = (i + j) as u64;
+        }
+        std::hint::black_box(&buffer);
+    }
+    let heap = start.elapsed();
+    (stack, heap)
+}
+
+fn stack_vs_heap_example() {
+    let (stack, heap) = bench_stack_vs_heap(200);
+    println!("stack vs heap: 200 fills of 1024 u64, stack {stack:?}, heap {heap:?}");
+}
+
+fn main() {
+    bump_allocator_example();
+    arena_example();
+    pool_allocator_example();
+    stack_frames_example();
+    counting_allocator_example();
+    realloc_example();
+    heap_sim_example();
+    dangling_example();
+    layout_example();
+    resettable_arena_example();
+    recursion_guard_example();
+    stack_vs_heap_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bump_allocator() {
+        // The buffers of a few Vecs, the offset only grows.
+        let bump = BumpAllocator::<4096>::new();
+        let numbers = Layout::array::<u64>(100).unwrap();
+        let text = Layout::array::<u8>(1000).unwrap();
+        unsafe {
+            let a = bump.alloc(numbers);
+            assert!(!a.is_null());
+            let after_numbers = bump.used();
+            assert!(after_numbers >= 100 * 8);
+            let b = bump.alloc(text);
+            assert!(!b.is_null());
+            let after_text = bump.used();
+            assert!(after_text >= after_numbers + 1000);
+            bump.dealloc(a, numbers);
+            bump.dealloc(b, text);
+            assert_eq!(bump.used(), after_text);
+        }
+
+        // A small allocator to check alignment, exhaustion and zero sizes.
+        let small = BumpAllocator::<64>::new();
+        unsafe {
+            let a = small.alloc(Layout::from_size_align(3, 1).unwrap());
+            assert!(!a.is_null());
+            assert_eq!(small.used(), 3);
+            let b = small.alloc(Layout::from_size_align(8, 8).unwrap());
+            assert_eq!(b as usize % 8, 0);
+            assert!(b as usize >= a as usize + 3);
+            let used = small.used();
+            assert!(small
+                .alloc(Layout::from_size_align(64, 1).unwrap())
+                .is_null());
+            assert_eq!(small.used(), used);
+            let empty = small.alloc(Layout::from_size_align(0, 16).unwrap());
+            assert!(!empty.is_null());
+            assert_eq!(empty as usize % 16, 0);
+            assert_eq!(small.used(), used);
+        }
+    }
+
+    #[test]
+    fn arena() {
+        let arena = Arena::new(256);
+        let refs: Vec<&u64> = (0..10_000u64).map(|i| arena.alloc(i * i)).collect();
+        assert_eq!(arena.chunk_count(), 10_000usize.div_ceil(256));
+        for (i, value) in refs.iter().enumerate() {
+            assert_eq!(**value, (i * i) as u64);
+        }
+
+        let names = Arena::new(1);
+        let first = names.alloc(String::from("first"));
+        let second = names.alloc(String::from("second"));
+        assert_eq!(names.chunk_count(), 2);
+        assert_eq!(format!("{first} {second}"), "first second");
+    }
+
+    #[test]
+    fn pool_allocator() {
+        let mut pool = PoolAllocator::new(32, 4);
+        let blocks: Vec<*mut u8> = (0..4).map(|_| pool.allocate().unwrap()).collect();
+        assert_eq!(pool.allocate(), None);
+        for (i, &block) in blocks.iter().enumerate() {
+            assert_eq!(block as usize - blocks[0] as usize, i * 32);
+            unsafe { block.write_bytes(i as u8, 32) };
+        }
+
+        pool.deallocate(blocks[2]).unwrap();
+        assert_eq!(pool.deallocate(blocks[2]), Err(PoolError::DoubleFree(2)));
+        let reused = pool.allocate().unwrap();
+        assert_eq!(reused, blocks[2]);
+        assert_eq!(pool.allocate(), None);
+        // The other blocks weren't touched by the free list.
+        assert_eq!(unsafe { *blocks[3].add(31) }, 3);
+
+        let outside = 0u8;
+        assert_eq!(
+            pool.deallocate(&outside as *const u8 as *mut u8),
+            Err(PoolError::ForeignPointer)
+        );
+        assert_eq!(
+            pool.deallocate(unsafe { blocks[1].add(1) }),
+            Err(PoolError::ForeignPointer)
+        );
+    }
+
+    #[test]
+    fn stack_frames() {
+        let main_frame = trace_frame("main");
+        // black_box hides the constant argument, otherwise the optimizer computes 9 in advance
+        // and turns the call of trace_frame into a jump that reuses the frame of traced_f.
+        let (x, f_frame) = traced_f(std::hint::black_box(2));
+        assert_eq!(x, 9);
+        assert_eq!(f(2), 9);
+        let grows_down = cfg!(any(
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "arm",
+            target_arch = "aarch64",
+            target_arch = "riscv64"
+        ));
+        if grows_down {
+            assert!(f_frame < main_frame);
+        }
+    }
+
+    #[test]
+    fn counting_allocator() {
+        // The other tests allocate on their own threads at the same time, so the counters of
+        // GLOBAL can only be checked to grow at least by what this test does.
+        let (allocated, freed) = (GLOBAL.allocated_bytes(), GLOBAL.freed_bytes());
+        let mut numbers: Vec<u32> = Vec::with_capacity(1000);
+        numbers.extend(0..1000);
+        assert!(GLOBAL.allocated_bytes() >= allocated + 4000);
+        drop(numbers);
+        assert!(GLOBAL.freed_bytes() >= freed + 4000);
+
+        // Dropping a Vec gives all its bytes back. An allocation of another test in between
+        // moves the count, so the check is repeated until a round sees only this Vec.
+        let returns_to_baseline = (0..1000).any(|_| {
+            let baseline = GLOBAL.live_bytes();
+            let numbers: Vec<u64> = vec![7; 512];
+            let with_vec = GLOBAL.live_bytes();
+            drop(numbers);
+            with_vec.wrapping_sub(baseline) == 4096 && GLOBAL.live_bytes() == baseline
+        });
+        assert!(returns_to_baseline);
+
+        // The exact accounting is checked on a wrapper nobody else uses.
+        let system = CountingAllocator::new(System);
+        let layout = Layout::new::<[u64; 16]>();
+        unsafe {
+            let block = system.alloc(layout);
+            assert!(!block.is_null());
+            assert_eq!(system.live_bytes(), 128);
+            let block = system.realloc(block, layout, 256);
+            assert_eq!(system.live_bytes(), 256);
+            let grown = Layout::from_size_align(256, layout.align()).unwrap();
+            let block = system.realloc(block, grown, 64);
+            assert_eq!(system.live_bytes(), 64);
+            system.dealloc(block, Layout::from_size_align(64, layout.align()).unwrap());
+        }
+        assert_eq!((system.allocated_bytes(), system.freed_bytes()), (448, 448));
+        assert_eq!(system.live_bytes(), 0);
+    }
+
+    #[test]
+    fn realloc() {
+        let pointers = observe_realloc();
+        assert!(pointers.len() >= 2);
+
+        let history = push_history(100);
+        for pair in history.windows(2) {
+            let ((old_ptr, old_cap), (new_ptr, new_cap)) = (pair[0], pair[1]);
+            assert!(new_cap >= old_cap);
+            if new_ptr != old_ptr {
+                assert!(new_cap > old_cap);
+            }
+        }
+        assert_eq!(history.last().map(|&(_, cap)| cap), Some(128));
+    }
+
+    #[test]
+    fn heap_sim() {
+        let mut buffer = [0u8; 256];
+        let (mut heap, blocks) = fragmented_heap(&mut buffer);
+        assert_eq!(blocks, vec![8, 64, 120, 176]);
+        assert_eq!(
+            heap.blocks(),
+            vec![
+                (0, 56, true),
+                (56, 56, false),
+                (112, 56, true),
+                (168, 56, true),
+                (224, 32, false)
+            ]
+        );
+
+        // Fragmentation: one hole of 56 bytes isn't enough for 90 bytes.
+        assert_eq!(heap.alloc(90, 8), None);
+        assert_eq!(heap.free(blocks[1]), Err(HeapError::DoubleFree(blocks[1])));
+        assert_eq!(
+            heap.free(blocks[1] + 3),
+            Err(HeapError::InvalidOffset(blocks[1] + 3))
+        );
+
+        // Freeing the neighbour merges the two holes and the big allocation fits.
+        heap.free(blocks[2]).unwrap();
+        assert_eq!(heap.blocks()[1], (56, 112, false));
+        assert_eq!(heap.alloc(90, 8), Some(blocks[1]));
+
+        // Freeing everything leaves a single free block again.
+        for offset in [blocks[0], blocks[1], blocks[3]] {
+            heap.free(offset).unwrap();
+        }
+        assert_eq!(heap.blocks(), vec![(0, 256, false)]);
+
+        let aligned = heap.alloc(10, 64).unwrap();
+        assert_eq!(aligned % 64, 0);
+        assert_eq!(heap.blocks()[0], (0, aligned - TAG, false));
+        heap.free(aligned).unwrap();
+        assert_eq!(heap.blocks(), vec![(0, 256, false)]);
+    }
+
+    #[test]
+    fn dangling() {
+        for _ in 0..3 {
+            assert_eq!(safe_alternative(), "hello");
+        }
+    }
+
+    #[test]
+    fn layout() {
+        let report = layout_report();
+        let word = size_of::<usize>();
+        let lookup = |name: &str| report.iter().find(|(n, _, _)| *n == name).unwrap();
+        assert_eq!(lookup("u8").1, 1);
+        assert_eq!(lookup("char").1, 4);
+        assert_eq!(lookup("&str").1, 2 * word);
+        assert_eq!(lookup("Vec<u8>").1, 3 * word);
+        assert_eq!(lookup("Option<Box<u8>>").1, word);
+        assert_eq!(lookup("Option<u32>").1, 8);
+        // The fields take 11 bytes, rounded up to the alignment of the u64 (4 on some 32-bit
+        // targets, 8 on the 64-bit ones).
+        assert_eq!(
+            lookup("Sample").1,
+            11usize.next_multiple_of(align_of::<u64>())
+        );
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(lookup("Sample").1, 16);
+        for (_, size, align) in &report {
+            assert!(size.is_multiple_of(*align));
+        }
+    }
+
+    #[test]
+    fn resettable_arena() {
+        let mut arena = ResettableArena::with_capacity(4);
+        let mut first_addresses = Vec::new();
+        for round in 0..3 {
+            let a = arena.alloc(format!("round {round}")).unwrap();
+            let b = arena.alloc(String::from("scratch")).unwrap();
+            a.push('!');
+            b.clear();
+            assert_eq!(a.as_str(), format!("round {round}!"));
+            first_addresses.push(a as *mut String as usize);
+            // let stale = &*a; arena.reset(); stale.len() -> error[E0502]
+            arena.reset();
+        }
+        assert!(first_addresses.windows(2).all(|w| w[0] == w[1]));
+
+        for i in 0..4 {
+            assert!(arena.alloc(i.to_string()).is_some());
+        }
+        assert!(arena.alloc(String::new()).is_none());
+    }
+
+    #[test]
+    fn recursion_guard() {
+        let guard = RecursionGuard::new();
+        assert_eq!(fib_guarded(20, &guard, 1024 * 1024), Ok(6765));
+
+        // 10000 nested calls would need much more than 4 KiB of stack.
+        let error = fib_guarded(10_000, &guard, 4096).unwrap_err();
+        assert!(error.used > error.limit);
+        assert_eq!(error.limit, 4096);
+    }
+
+    #[test]
+    fn stack_vs_heap() {
+        let (stack, heap) = bench_stack_vs_heap(200);
+        assert!(stack > Duration::ZERO && heap > Duration::ZERO);
+        assert!(stack < Duration::from_secs(10) && heap < Duration::from_secs(10));
+    }
+}
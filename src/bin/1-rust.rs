@@ -230,3 +230,6 @@
 //
 // https://rustjobs.dev/
 
+// This file only contains notes, main is empty so that the package builds: cargo build and
+// cargo test compile every binary in src/bin.
+fn main() {}
@@ -112,3 +112,502 @@
 // The self parameter in data structures can also have Box<Self>, Rc<Self> or Arc<Self> type but
 // must be explicitly used.
 // counter only works for weak references.
+//
+// -------------- Examples ----------------
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::{Rc, Weak};
+
+// Observable value
+//
+// Rc makes the value shared (every clone of the Observable sees the same value and the same
+// subscribers), RefCell makes it modifiable through the shared pointer and Box<dyn Fn> allows
+// closures of different types to be stored in the same Vec.
+// While the subscribers run the RefCell is borrowed immutably: they can read the value with
+// get() but calling set() from inside a subscriber would panic at runtime (already borrowed).
+type Subscriber<T> = Box<dyn Fn(&T)>;
+
+#[derive(Clone)]
+struct Observable<T: Clone> {
+    inner: Rc<RefCell<(T, Vec<Subscriber<T>>)>>,
+}
+
+impl<T: Clone> Observable<T> {
+    fn new(value: T) -> Self {
+        Observable {
+            inner: Rc::new(RefCell::new((value, Vec::new()))),
+        }
+    }
+
+    fn get(&self) -> T {
+        self.inner.borrow().0.clone()
+    }
+
+    fn set(&self, value: T) {
+        self.inner.borrow_mut().0 = value;
+        let inner = self.inner.borrow();
+        for subscriber in &inner.1 {
+            subscriber(&inner.0);
+        }
+    }
+
+    fn subscribe(&self, f: impl Fn(&T) + 'static) {
+        self.inner.borrow_mut().1.push(Box::new(f));
+    }
+}
+
+fn observable_example() {
+    let temperature = Observable::new(20);
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    {
+        let seen = Rc::clone(&seen);
+        temperature.subscribe(move |t| seen.borrow_mut().push(*t));
+    }
+    temperature.set(21);
+    temperature.set(23);
+    println!(
+        "observable: value {}, the subscriber saw {:?}",
+        temperature.get(),
+        seen.borrow()
+    );
+}
+
+// Persistent list
+//
+// push_front() doesn't modify the list: it returns a new list whose first node points to the
+// old first node, so the two lists share the whole tail and the old one is still valid and
+// unchanged. Sharing is cheap because a node is never modified after being created and Rc
+// counts how many lists (or nodes) point to it.
+// The default Drop would free the nodes recursively, one stack frame per node, so a long list
+// could overflow the stack: the custom Drop walks the list in a loop and stops at the first node
+// still shared with some other list.
+struct Node<T> {
+    value: T,
+    next: Option<Rc<Node<T>>>,
+}
+
+struct List<T> {
+    head: Option<Rc<Node<T>>>,
+}
+
+impl<T> List<T> {
+    fn new() -> Self {
+        List { head: None }
+    }
+
+    fn push_front(&self, value: T) -> List<T> {
+        List {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+        }
+    }
+
+    fn tail(&self) -> List<T> {
+        List {
+            head: self.head.as_ref().and_then(|node| node.next.clone()),
+        }
+    }
+
+    fn iter(&self) -> ListIter<'_, T> {
+        ListIter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+struct ListIter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for ListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+fn persistent_list_example() {
+    let base = List::new().push_front(3).push_front(2);
+    let a = base.push_front(1);
+    let b = base.push_front(10);
+    println!(
+        "persistent list: {:?} and {:?} share the nodes of {:?}, tail of a {:?}",
+        a.iter().collect::<Vec<_>>(),
+        b.iter().collect::<Vec<_>>(),
+        base.iter().collect::<Vec<_>>(),
+        a.tail().iter().collect::<Vec<_>>()
+    );
+}
+
+// String interner
+//
+// Many equal strings (identifiers in a compiler, tags, keys read from a file) can share a single
+// allocation: intern() returns an Rc<str> pointing to the copy already stored if there is one and
+// stores a new copy only the first time a string is seen.
+// HashSet<Rc<str>> is a HashMap<Rc<str>, ()>, and since Rc<str> implements Borrow<str> the set
+// can be searched with a plain &str without allocating anything.
+// Comparing two interned strings can then be done by pointer with Rc::ptr_eq.
+struct Interner {
+    strings: HashSet<Rc<str>>,
+}
+
+impl Interner {
+    fn new() -> Self {
+        Interner {
+            strings: HashSet::new(),
+        }
+    }
+
+    fn intern(&mut self, s: &str) -> Rc<str> {
+        if let Some(interned) = self.strings.get(s) {
+            return Rc::clone(interned);
+        }
+        let interned: Rc<str> = Rc::from(s);
+        self.strings.insert(Rc::clone(&interned));
+        interned
+    }
+
+    fn len(&self) -> usize {
+        self.strings.len()
+    }
+}
+
+fn interner_example() {
+    let mut interner = Interner::new();
+    let first = interner.intern("rust");
+    let again = interner.intern(&String::from("rust"));
+    let other = interner.intern("go");
+
+    println!(
+        "interner: {} distinct strings, {first:?} shared {}, {other:?} shared {}",
+        interner.len(),
+        Rc::ptr_eq(&first, &again),
+        Rc::ptr_eq(&first, &other)
+    );
+}
+
+// Doubly linked list
+//
+// If both links between two nodes were Rc, every pair of nodes would form a cycle: each node
+// keeps the strong counter of the other above zero and none of them is ever freed. The links
+// toward the end are strong (they own the next node) and the links toward the start are Weak,
+// so the first node is owned only by the list and dropping it frees the whole chain.
+// RefCell is needed because adding a node modifies the links of a node owned through an Rc.
+type Link<T> = Option<Rc<RefCell<DNode<T>>>>;
+
+struct DNode<T> {
+    value: T,
+    next: Link<T>,
+    prev: Option<Weak<RefCell<DNode<T>>>>,
+}
+
+struct Dlist<T> {
+    head: Link<T>,
+    tail: Link<T>,
+}
+
+impl<T> Dlist<T> {
+    fn new() -> Self {
+        Dlist {
+            head: None,
+            tail: None,
+        }
+    }
+
+    fn push_back(&mut self, value: T) {
+        let node = Rc::new(RefCell::new(DNode {
+            value,
+            next: None,
+            prev: None,
+        }));
+        match self.tail.take() {
+            Some(old_tail) => {
+                node.borrow_mut().prev = Some(Rc::downgrade(&old_tail));
+                old_tail.borrow_mut().next = Some(Rc::clone(&node));
+            }
+            None => self.head = Some(Rc::clone(&node)),
+        }
+        self.tail = Some(node);
+    }
+
+    fn push_front(&mut self, value: T) {
+        let node = Rc::new(RefCell::new(DNode {
+            value,
+            next: None,
+            prev: None,
+        }));
+        match self.head.take() {
+            Some(old_head) => {
+                old_head.borrow_mut().prev = Some(Rc::downgrade(&node));
+                node.borrow_mut().next = Some(old_head);
+            }
+            None => self.tail = Some(Rc::clone(&node)),
+        }
+        self.head = Some(node);
+    }
+}
+
+impl<T: Clone> Dlist<T> {
+    // The values are cloned out of the nodes since a reference couldn't outlive the borrow of
+    // the RefCell.
+    fn iter_forward(&self) -> impl Iterator<Item = T> {
+        let mut next = self.head.clone();
+        std::iter::from_fn(move || {
+            let node = next.take()?;
+            let node = node.borrow();
+            next = node.next.clone();
+            Some(node.value.clone())
+        })
+    }
+
+    fn iter_backward(&self) -> impl Iterator<Item = T> {
+        let mut prev = self.tail.clone();
+        std::iter::from_fn(move || {
+            let node = prev.take()?;
+            let node = node.borrow();
+            prev = node.prev.as_ref().and_then(Weak::upgrade);
+            Some(node.value.clone())
+        })
+    }
+}
+
+// Unlinking one node at a time avoids a recursive drop as deep as the list.
+impl<T> Drop for Dlist<T> {
+    fn drop(&mut self) {
+        self.tail.take();
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            next = node.borrow_mut().next.take();
+        }
+    }
+}
+
+fn dlist_example() {
+    let mut list = Dlist::new();
+    list.push_back(2);
+    list.push_back(3);
+    list.push_front(1);
+    list.push_front(0);
+    println!(
+        "doubly linked list: {:?} backwards {:?}",
+        list.iter_forward().collect::<Vec<_>>(),
+        list.iter_backward().collect::<Vec<_>>()
+    );
+}
+
+// Zero-copy parsing with Cow
+//
+// Most inputs contain no escape sequence at all and the result would be identical to the input,
+// so unescape() returns Cow::Borrowed pointing to the input itself and allocates a new Vec
+// (Cow::Owned) only when at least one "\n", "\t" or "\\" has to be replaced.
+// The caller uses the result through Deref in the same way in both cases.
+// Unknown escape sequences and a trailing backslash are kept as they are.
+fn unescape(input: &[u8]) -> Cow<'_, [u8]> {
+    let Some(first) = input.iter().position(|&b| b == b'\\') else {
+        return Cow::Borrowed(input);
+    };
+    let mut output = input[..first].to_vec();
+    let mut bytes = input[first..].iter().copied();
+    while let Some(byte) = bytes.next() {
+        if byte != b'\\' {
+            output.push(byte);
+            continue;
+        }
+        match bytes.next() {
+            Some(b'n') => output.push(b'\n'),
+            Some(b't') => output.push(b'\t'),
+            Some(b'\\') => output.push(b'\\'),
+            Some(other) => output.extend_from_slice(&[b'\\', other]),
+            None => output.push(b'\\'),
+        }
+    }
+    Cow::Owned(output)
+}
+
+fn cow_example() {
+    let plain = b"no escapes here";
+    let result = unescape(plain);
+    println!(
+        "cow: {:?} borrowed {}",
+        String::from_utf8_lossy(&result),
+        matches!(result, Cow::Borrowed(_))
+    );
+
+    let escaped = br"name\tvalue\nnext\\line";
+    let result = unescape(escaped);
+    println!(
+        "cow: {:?} borrowed {}",
+        String::from_utf8_lossy(&result),
+        matches!(result, Cow::Borrowed(_))
+    );
+}
+
+fn main() {
+    observable_example();
+    persistent_list_example();
+    interner_example();
+    dlist_example();
+    cow_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    // Increments a shared counter when dropped.
+    #[derive(Clone)]
+    struct DropCounter {
+        id: u32,
+        drops: Rc<Cell<usize>>,
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    #[test]
+    fn observable() {
+        let temperature = Observable::new(20);
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let calls = Rc::new(Cell::new(0));
+
+        {
+            let seen = Rc::clone(&seen);
+            temperature.subscribe(move |t| seen.borrow_mut().push(*t));
+        }
+        {
+            let calls = Rc::clone(&calls);
+            temperature.subscribe(move |_| calls.set(calls.get() + 1));
+        }
+        // Nobody is notified on subscription, only on changes.
+        assert!(seen.borrow().is_empty());
+
+        temperature.set(21);
+        // A clone shares value and subscribers.
+        let same = temperature.clone();
+        same.set(23);
+        assert_eq!(*seen.borrow(), vec![21, 23]);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(temperature.get(), 23);
+    }
+
+    #[test]
+    fn persistent_list() {
+        let base = List::new().push_front(3).push_front(2);
+        let a = base.push_front(1);
+        let b = base.push_front(10);
+
+        assert_eq!(base.iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(b.iter().copied().collect::<Vec<_>>(), vec![10, 2, 3]);
+
+        // The node holding 2 is pointed by base, by the first node of a and by the first node of b.
+        let shared = base.head.as_ref().unwrap();
+        assert_eq!(Rc::strong_count(shared), 3);
+        // The node holding 3 is only pointed by the node holding 2.
+        assert_eq!(Rc::strong_count(shared.next.as_ref().unwrap()), 1);
+
+        drop(b);
+        let shared = base.head.as_ref().unwrap();
+        assert_eq!(Rc::strong_count(shared), 2);
+        assert!(Rc::ptr_eq(a.tail().head.as_ref().unwrap(), shared));
+
+        // Dropping a long list doesn't overflow the stack.
+        let mut long = List::new();
+        for i in 0..1_000_000 {
+            long = long.push_front(i);
+        }
+        assert_eq!(long.iter().count(), 1_000_000);
+        drop(long);
+    }
+
+    #[test]
+    fn interner() {
+        let mut interner = Interner::new();
+        let first = interner.intern("rust");
+        let again = interner.intern(&String::from("rust"));
+        let other = interner.intern("go");
+
+        assert!(Rc::ptr_eq(&first, &again));
+        assert!(!Rc::ptr_eq(&first, &other));
+        assert_eq!(&*again, "rust");
+        assert_eq!(interner.len(), 2);
+        // One reference is kept by the interner plus the two returned.
+        assert_eq!(Rc::strong_count(&first), 3);
+    }
+
+    #[test]
+    fn dlist() {
+        let mut list = Dlist::new();
+        list.push_back(2);
+        list.push_back(3);
+        list.push_front(1);
+        list.push_front(0);
+        assert_eq!(list.iter_forward().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+        assert_eq!(list.iter_backward().collect::<Vec<_>>(), vec![3, 2, 1, 0]);
+        assert_eq!(Dlist::<i32>::new().iter_forward().count(), 0);
+
+        let drops = Rc::new(Cell::new(0));
+        let mut list = Dlist::new();
+        for id in 0..100 {
+            let counter = DropCounter {
+                id,
+                drops: Rc::clone(&drops),
+            };
+            if id % 2 == 0 {
+                list.push_back(counter);
+            } else {
+                list.push_front(counter);
+            }
+        }
+        let ids: Vec<u32> = list.iter_forward().map(|c| c.id).collect();
+        assert_eq!(ids.len(), 100);
+        assert_eq!((ids[0], ids[99]), (99, 98));
+        // The clones made while iterating have been dropped already.
+        let before = drops.get();
+        let first = Rc::downgrade(list.head.as_ref().unwrap());
+        drop(list);
+        // Every node has been freed: no cycle kept any of them alive.
+        assert_eq!(drops.get() - before, 100);
+        assert!(first.upgrade().is_none());
+    }
+
+    #[test]
+    fn cow() {
+        let plain = b"no escapes here";
+        let result = unescape(plain);
+        assert!(matches!(result, Cow::Borrowed(_)));
+        assert_eq!(result.as_ptr(), plain.as_ptr());
+
+        let escaped = br"name\tvalue\nnext\\line";
+        let result = unescape(escaped);
+        assert!(matches!(result, Cow::Owned(_)));
+        assert_eq!(&*result, b"name\tvalue\nnext\\line");
+
+        assert_eq!(&*unescape(br"\q and \"), br"\q and \");
+        assert!(matches!(unescape(b""), Cow::Borrowed(_)));
+    }
+}
@@ -88,5 +88,347 @@
 // test.
 // Also ignore annotated test can be executed using the command "cargo test -- --ignored".
 //
+// DOCTESTS
 //
+// The code blocks written in the documentation comments (///) of a library are compiled and
+// executed by cargo test as doctests, each one as if it was a small program importing the crate.
+// A doctest should contain an assert so that the documentation fails when it gets out of date:
 //
+// /// Counts the occurrences of each word.
+// ///
+// /// ```
+// /// let counts = esercizi_malnati::collections::word_count("a b a");
+// /// assert_eq!(counts["a"], 2);
+// /// ```
+// pub fn word_count(text: &str) -> HashMap<String, usize> { ... }
+//
+// Lines starting with "# " are executed but hidden in the rendered documentation, and the
+// block can be annotated as ```should_panic, ```no_run or ```ignore.
+// Doctests are run only for library targets: the documentation comments of the binaries in
+// src/bin are not tested, so the doctests of this package are in the collections and errors
+// modules of its library (src/lib.rs). The command "cargo test --doc" runs only the doctests.
+//
+//
+//
+// -------------- Examples ----------------
+//
+
+// SORTING MODULES
+// Three modules each sorting in a different way: in-order visit of a binary search tree,
+// draining a BinaryHeap and quicksort. They live in the library crate of the package
+// (src/lib.rs declares them in src/sorting.rs) so the binary imports them with the name of the
+// package, like any external crate. The integration tests in tests/sorting.rs feed the same
+// seeded random inputs to all of them through their public function and check they agree with
+// Vec::sort, so a bug in one of the paths shows up as a divergence.
+use esercizi_malnati::sorting::{bst, heap, quick};
+
+type Sorter = fn(&[i32]) -> Vec<i32>;
+
+fn sorting_example() {
+    let sorters: [(&str, Sorter); 3] = [
+        ("bst", bst::sort),
+        ("heap", heap::sort),
+        ("quick", quick::sort),
+    ];
+    let input = [5, -3, 8, 0, 5, 12, -7, 1];
+    for (name, sort) in sorters {
+        println!("sorting: {name} {:?}", sort(&input));
+    }
+}
+
+// CONDITIONAL COMPILATION
+// #[cfg(...)] removes an item from the compilation when its condition is false, so two
+// versions of the same function can exist and only one of them ends up in the binary.
+// Here the condition is the "bench" feature: by default measured_sort just calls quicksort,
+// with the feature it is replaced by a heavier version that times every sorter and checks
+// they agree. cfg!(...) evaluates the same condition as a bool inside an expression.
+// Run with: cargo run --bin 11-module_test --features bench
+#[cfg(not(feature = "bench"))]
+const SORT_PATH: &str = "lightweight";
+
+#[cfg(feature = "bench")]
+const SORT_PATH: &str = "instrumented";
+
+#[cfg(not(feature = "bench"))]
+fn measured_sort(values: &[i32]) -> Vec<i32> {
+    quick::sort(values)
+}
+
+#[cfg(feature = "bench")]
+fn measured_sort(values: &[i32]) -> Vec<i32> {
+    use std::time::Instant;
+
+    let sorters: [(&str, Sorter); 3] = [
+        ("bst", bst::sort),
+        ("heap", heap::sort),
+        ("quick", quick::sort),
+    ];
+    let mut results = Vec::new();
+    for (name, sort) in sorters {
+        let start = Instant::now();
+        let sorted = sort(values);
+        println!("  {name}: {} values in {:?}", values.len(), start.elapsed());
+        results.push(sorted);
+    }
+    assert!(results.windows(2).all(|w| w[0] == w[1]));
+    results.pop().unwrap()
+}
+
+fn cfg_example() {
+    // Scrambled but deterministic values: 7919 is prime so every value in 0..10_007 appears once.
+    let input: Vec<i32> = (0..10_000).map(|i| (i * 7919) % 10_007).collect();
+    let sorted = measured_sort(&input);
+    println!(
+        "cfg: compiled the {SORT_PATH} sort path, sorted {} values from {} to {}",
+        sorted.len(),
+        sorted[0],
+        sorted[sorted.len() - 1]
+    );
+}
+
+// ISOLATING PANICS
+// A #[should_panic] test checks that something panics, but inside a normal function a panic
+// would stop everything after it. run_isolated runs a closure under catch_unwind and turns a
+// panic into an Err with its message, so the caller can assert on it and go on.
+// The payload of a panic is a Box<dyn Any>: panic!("literal") carries a &str, a panic with
+// format arguments carries a String, anything else (std::panic::panic_any) has no message.
+// UnwindSafe is a marker that the closure doesn't capture something that could be left half
+// modified by the panic (like a &mut), AssertUnwindSafe opts out when the caller knows better.
+// catch_unwind doesn't stop the panic hook from printing the message, the example silences it
+// while it runs and puts the default hook back at the end.
+fn run_isolated<F: FnOnce() -> R + std::panic::UnwindSafe, R>(f: F) -> Result<R, String> {
+    std::panic::catch_unwind(f).map_err(|payload| {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            String::from("panic with a non-string payload")
+        }
+    })
+}
+
+fn run_isolated_example() {
+    std::panic::set_hook(Box::new(|_| {}));
+
+    println!("run isolated: {:?}", run_isolated(|| 2 + 2));
+    let values: Vec<i32> = (1..=3).collect();
+    println!("run isolated: {:?}", run_isolated(|| values[10]));
+
+    drop(std::panic::take_hook());
+    println!("run isolated: panics turned into errors, the program went on");
+}
+
+// PROPERTY TESTING
+// A unit test checks the inputs its author thought of, a property test states something that
+// must hold for every input (the elements of a SortedVec stay in order, the union of two sets
+// doesn't depend on which one comes first, two connected elements are connected both ways) and
+// checks it on many random ones. When an input breaks the property it is shrunk: proptest tries
+// smaller and simpler versions of it while the property keeps failing, so the reported
+// counterexample is a small one instead of 30 random operations.
+// proptest is a dev-dependency, so only #[cfg(test)] code can use it: the properties are in
+// the tests module at the end of the file. The proptest! macro turns each function into a
+// #[test] whose arguments are generated by the strategy after "in" (a type after ':' uses
+// any::<T>()). The structures checked are the BitSet, SortedVec and UnionFind of the
+// collections module of the library, 8-collections shows the first two at work.
+
+fn main() {
+    sorting_example();
+    cfg_example();
+    run_isolated_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use esercizi_malnati::collections::{BitSet, SortedVec, UnionFind};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use proptest::test_runner::{Config, TestError, TestRng, TestRunner};
+
+    #[test]
+    fn cfg() {
+        let expected_path = if cfg!(feature = "bench") {
+            "instrumented"
+        } else {
+            "lightweight"
+        };
+        assert_eq!(SORT_PATH, expected_path);
+
+        let input: Vec<i32> = (0..10_000).map(|i| (i * 7919) % 10_007 - 5_000).collect();
+        let mut expected = input.clone();
+        expected.sort();
+        assert_eq!(measured_sort(&input), expected);
+    }
+
+    #[test]
+    fn run_isolated_works() {
+        assert_eq!(run_isolated(|| 2 + 2), Ok(4));
+        assert_eq!(
+            run_isolated(|| -> i32 { panic!("boom") }),
+            Err(String::from("boom"))
+        );
+        let index = 7;
+        assert_eq!(
+            run_isolated(move || -> i32 { panic!("index {index} out of range") }),
+            Err(String::from("index 7 out of range"))
+        );
+        let values: Vec<i32> = (1..=3).collect();
+        let result = run_isolated(|| values[10]);
+        assert_eq!(
+            result,
+            Err(String::from(
+                "index out of bounds: the len is 3 but the index is 10"
+            ))
+        );
+        assert_eq!(
+            run_isolated(|| std::panic::panic_any(42)),
+            Err::<(), _>(String::from("panic with a non-string payload"))
+        );
+
+        let mut total = 0;
+        let result = run_isolated(std::panic::AssertUnwindSafe(|| {
+            total += 1;
+            total
+        }));
+        assert_eq!(result, Ok(1));
+    }
+
+    #[derive(Clone, Debug)]
+    enum Op {
+        Insert(i32),
+        Remove(i32),
+    }
+
+    // Few distinct values, so removes often find something and duplicates are common.
+    fn ops() -> impl Strategy<Value = Vec<Op>> {
+        vec(
+            prop_oneof![(0..20).prop_map(Op::Insert), (0..20).prop_map(Op::Remove)],
+            0..60,
+        )
+    }
+
+    const ELEMENTS: usize = 12;
+
+    fn bitset_of(elements: &[usize]) -> BitSet {
+        let mut set = BitSet::new();
+        for &i in elements {
+            set.set(i);
+        }
+        set
+    }
+
+    proptest! {
+        // After every operation the elements are sorted, and at the end they are the ones of
+        // a plain Vec that received the same operations (the model).
+        #[test]
+        fn sorted_vec_stays_sorted(ops in ops()) {
+            let mut sorted = SortedVec::new();
+            let mut model = Vec::new();
+            for op in ops {
+                match op {
+                    Op::Insert(value) => {
+                        sorted.insert(value);
+                        model.push(value);
+                    }
+                    Op::Remove(value) => {
+                        let position = model.iter().position(|&m| m == value);
+                        prop_assert_eq!(sorted.remove(&value).is_some(), position.is_some());
+                        if let Some(position) = position {
+                            model.swap_remove(position);
+                        }
+                    }
+                }
+                prop_assert!(sorted.as_slice().windows(2).all(|w| w[0] <= w[1]));
+            }
+            model.sort();
+            prop_assert_eq!(sorted.as_slice(), model.as_slice());
+        }
+
+        #[test]
+        fn bitset_contains_what_was_set(
+            elements in vec(0..300usize, 0..40),
+            cleared in 0..300usize,
+        ) {
+            let mut set = bitset_of(&elements);
+            prop_assert!((0..320).all(|i| set.contains(i) == elements.contains(&i)));
+            set.clear(cleared);
+            prop_assert!(!set.contains(cleared));
+            prop_assert!(set.iter().all(|i| elements.contains(&i)));
+        }
+
+        #[test]
+        fn bitset_operations_commute(
+            a in vec(0..300usize, 0..40),
+            b in vec(0..300usize, 0..40),
+        ) {
+            let (a_set, b_set) = (bitset_of(&a), bitset_of(&b));
+            let (mut a_or_b, mut b_or_a) = (a_set.clone(), b_set.clone());
+            a_or_b.union_with(&b_set);
+            b_or_a.union_with(&a_set);
+            prop_assert_eq!(a_or_b.iter().collect::<Vec<_>>(), b_or_a.iter().collect::<Vec<_>>());
+            prop_assert!(a_or_b.iter().all(|i| a.contains(&i) || b.contains(&i)));
+
+            let (mut a_and_b, mut b_and_a) = (a_set.clone(), b_set.clone());
+            a_and_b.intersect_with(&b_set);
+            b_and_a.intersect_with(&a_set);
+            prop_assert_eq!(a_and_b.iter().collect::<Vec<_>>(), b_and_a.iter().collect::<Vec<_>>());
+            prop_assert!(a_and_b.iter().all(|i| a.contains(&i) && b.contains(&i)));
+
+            // Every element is counted once by the union and once more by the intersection if
+            // it is in both sets.
+            prop_assert_eq!(
+                a_or_b.count_ones() + a_and_b.count_ones(),
+                a_set.count_ones() + b_set.count_ones()
+            );
+        }
+
+        // Connectivity is an equivalence relation: symmetric and transitive, and every pair
+        // passed to union ends up connected.
+        #[test]
+        fn union_find_connectivity(unions in vec((0..ELEMENTS, 0..ELEMENTS), 0..20)) {
+            let mut uf = UnionFind::new(ELEMENTS);
+            for &(a, b) in &unions {
+                uf.union(a, b);
+            }
+            let connected: Vec<Vec<bool>> = (0..ELEMENTS)
+                .map(|a| (0..ELEMENTS).map(|b| uf.connected(a, b)).collect())
+                .collect();
+            for &(a, b) in &unions {
+                prop_assert!(connected[a][b]);
+            }
+            for a in 0..ELEMENTS {
+                for b in 0..ELEMENTS {
+                    prop_assert_eq!(connected[a][b], connected[b][a]);
+                    for c in 0..ELEMENTS {
+                        prop_assert!(!(connected[a][b] && connected[b][c]) || connected[a][c]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shrinking() {
+        // A wrong property: with values in 0..20 a duplicate shows up quickly, and the up to 30
+        // random values are shrunk down to the two equal ones.
+        let config = Config {
+            failure_persistence: None,
+            ..Config::default()
+        };
+        let rng = TestRng::deterministic_rng(config.rng_algorithm);
+        let mut runner = TestRunner::new_with_rng(config, rng);
+        let result = runner.run(&vec(0..20i32, 0..30), |values| {
+            let sorted = quick::sort(&values);
+            prop_assert!(sorted.windows(2).all(|w| w[0] != w[1]));
+            Ok(())
+        });
+        match result {
+            Err(TestError::Fail(_, counterexample)) => {
+                assert_eq!(counterexample.len(), 2);
+                assert_eq!(counterexample[0], counterexample[1]);
+            }
+            other => panic!("the property should fail, got {other:?}"),
+        }
+    }
+}
@@ -632,3 +632,1523 @@
 // This approach removes the need of locks and general synchronization making threads work
 // in parallel on their own. If an actor receives an external message it proceeds to change it's
 // behaviour.
+//
+// ------------------------------------ EXAMPLES -------------------------------------------------
+
+#[cfg(feature = "crossbeam")]
+use crossbeam_epoch::{self as epoch, Atomic, Owned};
+#[cfg(feature = "parking_lot")]
+use esercizi_malnati::rwlock_cache::{prefilled, read_heavy_workload, ParkingLotCache, StdCache};
+use std::cell::{RefCell, UnsafeCell};
+use std::marker::PhantomData;
+#[cfg(feature = "crossbeam")]
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{fence, AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+// SEMAPHORE
+//
+// The standard library offers no semaphore even if both Windows and Linux kernels list it among
+// their primitives. It can be built with a Mutex guarding the number of free permits and a
+// Condvar on which the threads that find no permit left go to sleep.
+// acquire() uses wait_while() so a false notification can't make a thread take a permit that
+// doesn't exist, release() gives the permit back and wakes up one of the sleeping threads.
+struct Semaphore {
+    permits: Mutex<usize>,
+    cvar: Condvar,
+}
+
+impl Semaphore {
+    fn new(permits: usize) -> Self {
+        Semaphore {
+            permits: Mutex::new(permits),
+            cvar: Condvar::new(),
+        }
+    }
+
+    fn acquire(&self) {
+        let permits = self.permits.lock().unwrap();
+        let mut permits = self.cvar.wait_while(permits, |p| *p == 0).unwrap();
+        *permits -= 1;
+    }
+
+    fn release(&self) {
+        let mut permits = self.permits.lock().unwrap();
+        *permits += 1;
+        self.cvar.notify_one();
+    }
+}
+
+// Eight threads compete for the permits, each one records how many threads are inside the
+// critical section when it enters and the highest value ever seen is returned.
+fn max_inside_semaphore(permits: usize) -> usize {
+    let semaphore = Arc::new(Semaphore::new(permits));
+    let inside = Arc::new(AtomicUsize::new(0));
+    let max_inside = Arc::new(AtomicUsize::new(0));
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let semaphore = Arc::clone(&semaphore);
+            let inside = Arc::clone(&inside);
+            let max_inside = Arc::clone(&max_inside);
+            thread::spawn(move || {
+                semaphore.acquire();
+                let now = inside.fetch_add(1, Ordering::SeqCst) + 1;
+                max_inside.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+                inside.fetch_sub(1, Ordering::SeqCst);
+                semaphore.release();
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    max_inside.load(Ordering::SeqCst)
+}
+
+fn semaphore_example() {
+    println!(
+        "semaphore: at most {} threads inside with 3 permits",
+        max_inside_semaphore(3)
+    );
+}
+
+// LATCH
+//
+// A one-shot completion signal: every thread calling wait() sleeps until some thread calls
+// signal(), after that the latch stays open forever and wait() returns immediately.
+// The boolean is needed (rather than a bare Condvar) so that a thread arriving after the signal
+// doesn't wait for a notification that was already sent.
+struct Latch {
+    open: Mutex<bool>,
+    cvar: Condvar,
+}
+
+impl Latch {
+    fn new() -> Self {
+        Latch {
+            open: Mutex::new(false),
+            cvar: Condvar::new(),
+        }
+    }
+
+    fn wait(&self) {
+        let open = self.open.lock().unwrap();
+        let _open = self.cvar.wait_while(open, |open| !*open).unwrap();
+    }
+
+    fn signal(&self) {
+        let mut open = self.open.lock().unwrap();
+        *open = true;
+        self.cvar.notify_all();
+    }
+}
+
+fn latch_example() {
+    let latch = Arc::new(Latch::new());
+    let waiter = {
+        let latch = Arc::clone(&latch);
+        thread::spawn(move || latch.wait())
+    };
+    latch.signal();
+    waiter.join().unwrap();
+    println!("latch: waiter released by a single signal");
+}
+
+// COUNTDOWN LATCH
+//
+// Generalization of the latch above: it opens after count_down() has been called `count` times.
+// A coordinator thread can so wait for N workers without joining each one of them.
+struct CountdownLatch {
+    count: Mutex<usize>,
+    cvar: Condvar,
+}
+
+impl CountdownLatch {
+    fn new(count: usize) -> Self {
+        CountdownLatch {
+            count: Mutex::new(count),
+            cvar: Condvar::new(),
+        }
+    }
+
+    fn count_down(&self) {
+        let mut count = self.count.lock().unwrap();
+        if *count > 0 {
+            *count -= 1;
+            if *count == 0 {
+                self.cvar.notify_all();
+            }
+        }
+    }
+
+    fn wait(&self) {
+        let count = self.count.lock().unwrap();
+        let _count = self.cvar.wait_while(count, |count| *count > 0).unwrap();
+    }
+}
+
+fn countdown_latch_example() {
+    let latch = CountdownLatch::new(3);
+    thread::scope(|s| {
+        for _ in 0..3 {
+            s.spawn(|| latch.count_down());
+        }
+        latch.wait();
+    });
+    println!("countdown latch: coordinator released after 3 count_down() calls");
+}
+
+// THREAD LOCAL STORAGE
+//
+// The thread_local! macro declares a static that has a separate copy for each thread, it is
+// lazily initialized the first time a thread accesses it through with(). Since no other thread
+// can see it, it can be wrapped in a RefCell instead of a Mutex.
+// Workers push their partial results in the local buffer without any lock and only at the end
+// they lock the shared vector once to merge what they collected, so the contention on the
+// mutex goes from one lock per item to one lock per thread.
+thread_local! {
+    static LOCAL_BUFFER: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+fn accumulate_locally(items: &[u64], shared: &Mutex<Vec<u64>>, merges: &AtomicUsize) -> usize {
+    for item in items {
+        LOCAL_BUFFER.with(|buffer| buffer.borrow_mut().push(item * item));
+    }
+    let collected = LOCAL_BUFFER.with(|buffer| buffer.take());
+    shared.lock().unwrap().extend_from_slice(&collected);
+    merges.fetch_add(1, Ordering::SeqCst);
+    collected.len()
+}
+
+// Splits `data` among `threads` workers, returns how many items each of them collected in its
+// local buffer and the shared vector they merged into.
+fn square_in_threads(data: &[u64], threads: usize, merges: &AtomicUsize) -> (Vec<usize>, Vec<u64>) {
+    let shared = Mutex::new(Vec::new());
+    let local_sizes = thread::scope(|s| {
+        let handles: Vec<_> = data
+            .chunks(data.len() / threads)
+            .map(|chunk| s.spawn(|| accumulate_locally(chunk, &shared, merges)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    (local_sizes, shared.into_inner().unwrap())
+}
+
+fn thread_local_example() {
+    let data: Vec<u64> = (1..=1000).collect();
+    let merges = AtomicUsize::new(0);
+    let (_, merged) = square_in_threads(&data, 4, &merges);
+    println!(
+        "thread local: merged sum of squares {} with {} locks",
+        merged.iter().sum::<u64>(),
+        merges.load(Ordering::SeqCst)
+    );
+}
+
+// MAP-REDUCE WITH SCOPED THREADS
+//
+// The data is split in `workers` owned chunks, each chunk is moved into a scoped thread that
+// applies `map` to every element. Since the threads are scoped, `map` can be borrowed by all of
+// them (that's why it only needs to be Sync) instead of being cloned or wrapped in an Arc.
+// The partial results are then combined sequentially on the calling thread starting from
+// `identity`.
+fn map_reduce<T, M, R>(
+    data: Vec<T>,
+    workers: usize,
+    map: M,
+    reduce: impl Fn(R, R) -> R,
+    identity: R,
+) -> R
+where
+    T: Send,
+    M: Fn(T) -> R + Sync,
+    R: Send,
+{
+    let chunk_size = data.len().div_ceil(workers.max(1)).max(1);
+    let mut chunks: Vec<Vec<T>> = Vec::new();
+    let mut data = data.into_iter().peekable();
+    while data.peek().is_some() {
+        chunks.push(data.by_ref().take(chunk_size).collect());
+    }
+
+    let map = &map;
+    let mapped: Vec<Vec<R>> = thread::scope(|s| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| s.spawn(move || chunk.into_iter().map(map).collect::<Vec<R>>()))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    mapped.into_iter().flatten().fold(identity, reduce)
+}
+
+fn map_reduce_example() {
+    let data: Vec<u64> = (1..=100_000).collect();
+    let sum_of_squares = map_reduce(data, 8, |x| x * x, |a, b| a + b, 0);
+    let values: Vec<i64> = (0..50_000).map(|i| (i * 7919) % 10_007 - 5_000).collect();
+    let max = map_reduce(values, 3, |x| x, i64::max, i64::MIN);
+    println!("map reduce: sum of squares {}, max {}", sum_of_squares, max);
+}
+
+// OBJECT POOL
+//
+// Objects that are expensive to create (connections, big buffers) can be created once and
+// borrowed by the threads when needed. The free objects are in a Vec guarded by a Mutex,
+// acquire() waits on the Condvar while the Vec is empty and returns a guard that gives access
+// to the object; when the guard is dropped the object goes back in the Vec and one of the
+// waiting threads is woken up, so it can't be forgotten even if the thread panics.
+// Pool is a handle around an Arc so it can be cloned and moved into the threads.
+#[derive(Clone)]
+struct Pool<T> {
+    shared: Arc<(Mutex<Vec<T>>, Condvar)>,
+}
+
+struct PooledGuard<T> {
+    item: Option<T>,
+    shared: Arc<(Mutex<Vec<T>>, Condvar)>,
+}
+
+impl<T> Pool<T> {
+    fn new(items: Vec<T>) -> Self {
+        Pool {
+            shared: Arc::new((Mutex::new(items), Condvar::new())),
+        }
+    }
+
+    fn acquire(&self) -> PooledGuard<T> {
+        let (items, cvar) = &*self.shared;
+        let mut items = cvar
+            .wait_while(items.lock().unwrap(), |i| i.is_empty())
+            .unwrap();
+        PooledGuard {
+            item: items.pop(),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    fn available(&self) -> usize {
+        self.shared.0.lock().unwrap().len()
+    }
+}
+
+impl<T> Deref for PooledGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for PooledGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.item.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for PooledGuard<T> {
+    fn drop(&mut self) {
+        let (items, cvar) = &*self.shared;
+        if let Some(item) = self.item.take() {
+            items.lock().unwrap().push(item);
+            cvar.notify_one();
+        }
+    }
+}
+
+fn pool_example() {
+    let pool = Pool::new(vec![Vec::<usize>::new(), Vec::new(), Vec::new()]);
+    let mut buffer = pool.acquire();
+    buffer.push(1);
+    let available_while_held = pool.available();
+    drop(buffer);
+    println!(
+        "object pool: {available_while_held} of 3 buffers left while one is held, {} after",
+        pool.available()
+    );
+}
+
+// BROADCAST WITH A VERSION NUMBER
+//
+// One producer publishes values and many consumers want the most recent one, without queuing
+// every intermediate value. The value is in a RwLock and an AtomicU64 counts the
+// publications: a consumer remembers the last version it has seen and latest() returns None
+// with a single atomic load when nothing new has been published, so polling doesn't touch the
+// lock. The version is incremented while the write lock is held, so a reader holding the read
+// lock always finds a version that matches the value.
+struct Broadcaster<T: Clone> {
+    version: AtomicU64,
+    value: RwLock<T>,
+}
+
+impl<T: Clone> Broadcaster<T> {
+    fn new(initial: T) -> Self {
+        Broadcaster {
+            version: AtomicU64::new(0),
+            value: RwLock::new(initial),
+        }
+    }
+
+    fn publish(&self, value: T) {
+        let mut current = self.value.write().unwrap();
+        *current = value;
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn latest(&self, last_seen: u64) -> Option<(u64, T)> {
+        if self.version.load(Ordering::Acquire) == last_seen {
+            return None;
+        }
+        let value = self.value.read().unwrap();
+        Some((self.version.load(Ordering::Acquire), value.clone()))
+    }
+}
+
+fn broadcaster_example() {
+    let broadcaster = Broadcaster::new(0u64);
+    broadcaster.publish(1);
+    broadcaster.publish(2);
+    println!(
+        "broadcaster: after version 0 a consumer gets {:?}, after version 2 {:?}",
+        broadcaster.latest(0),
+        broadcaster.latest(2)
+    );
+}
+
+// LOCK-FREE STACK
+//
+// A Treiber stack: the stack is a linked list and the only shared state is the atomic pointer
+// to the head. push and pop read the head, prepare the new value and publish it with a
+// compare-and-swap that fails if another thread changed the head meanwhile, in that case
+// they retry. No thread ever waits for a lock held by another one.
+// The hard part is freeing a popped node: another thread may have read the same head just
+// before and still be reading its `next`. crossbeam-epoch solves it: every access happens
+// while the thread is pinned and defer_destroy frees the node only when all the threads
+// pinned at that moment have unpinned.
+// Run with: cargo run --bin 12-concurrency --features crossbeam
+#[cfg(feature = "crossbeam")]
+struct StackNode<T> {
+    value: ManuallyDrop<T>,
+    next: Atomic<StackNode<T>>,
+}
+
+#[cfg(feature = "crossbeam")]
+struct TreiberStack<T> {
+    head: Atomic<StackNode<T>>,
+}
+
+#[cfg(feature = "crossbeam")]
+impl<T> TreiberStack<T> {
+    fn new() -> Self {
+        TreiberStack {
+            head: Atomic::null(),
+        }
+    }
+
+    fn push(&self, value: T) {
+        let mut node = Owned::new(StackNode {
+            value: ManuallyDrop::new(value),
+            next: Atomic::null(),
+        });
+        let guard = epoch::pin();
+        loop {
+            let head = self.head.load(Ordering::Relaxed, &guard);
+            node.next.store(head, Ordering::Relaxed);
+            match self.head.compare_exchange(
+                head,
+                node,
+                Ordering::Release,
+                Ordering::Relaxed,
+                &guard,
+            ) {
+                Ok(_) => return,
+                Err(error) => node = error.new,
+            }
+        }
+    }
+
+    fn pop(&self) -> Option<T> {
+        let guard = epoch::pin();
+        loop {
+            let head = self.head.load(Ordering::Acquire, &guard);
+            let node = unsafe { head.as_ref() }?;
+            let next = node.next.load(Ordering::Relaxed, &guard);
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Relaxed, Ordering::Relaxed, &guard)
+                .is_ok()
+            {
+                // Only the thread that won the exchange moves the value out, the node memory
+                // is freed later without dropping the value again (ManuallyDrop).
+                unsafe {
+                    guard.defer_destroy(head);
+                    return Some(ManuallyDrop::into_inner(std::ptr::read(&node.value)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+fn treiber_stack_example() {
+    let stack = TreiberStack::new();
+    for value in 1..=3 {
+        stack.push(value);
+    }
+    let popped: Vec<u32> = std::iter::from_fn(|| stack.pop()).collect();
+    println!("treiber stack: pushed 1, 2, 3 and popped {popped:?}");
+}
+
+// SEQLOCK
+//
+// For small Copy data read very often and written rarely. The writer (only one at a time,
+// they are serialized by a Mutex) increments a sequence number before and after the write, so
+// it is odd while a write is in progress. A reader never blocks the writer: it reads the
+// sequence, copies the data and reads the sequence again, if it was odd or has changed the
+// copy may be torn (half old, half new) and the reader retries.
+// The copy races with the writer by design, so the data is stored as an array of AtomicU64
+// words: a racing plain read would be undefined behavior, while Relaxed atomic loads are
+// allowed to race and cost the same as plain loads on the common architectures. A torn copy
+// is made of valid words anyway and it is thrown away because the sequence changed. The
+// fences order the word accesses with the sequence number: in the reader the Acquire fence
+// keeps the loads of the words before the second load of the sequence, in the writer the
+// Release fence keeps the stores after the first increment.
+// T is converted to and from the N words, so any small Copy type can be stored.
+struct SeqLock<T, const N: usize> {
+    seq: AtomicUsize,
+    data: [AtomicU64; N],
+    writer: Mutex<()>,
+    _value: PhantomData<T>,
+}
+
+impl<T: Copy + From<[u64; N]> + Into<[u64; N]>, const N: usize> SeqLock<T, N> {
+    fn new(value: T) -> Self {
+        SeqLock {
+            seq: AtomicUsize::new(0),
+            data: value.into().map(AtomicU64::new),
+            writer: Mutex::new(()),
+            _value: PhantomData,
+        }
+    }
+
+    fn read(&self) -> (T, usize) {
+        let mut retries = 0;
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            if before.is_multiple_of(2) {
+                let words = std::array::from_fn(|i| self.data[i].load(Ordering::Relaxed));
+                fence(Ordering::Acquire);
+                if self.seq.load(Ordering::Relaxed) == before {
+                    return (T::from(words), retries);
+                }
+            }
+            retries += 1;
+            std::hint::spin_loop();
+        }
+    }
+
+    fn write(&self, value: T) {
+        let _writer = self.writer.lock().unwrap();
+        self.seq.fetch_add(1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        for (word, new) in self.data.iter().zip(value.into()) {
+            word.store(new, Ordering::Relaxed);
+        }
+        self.seq.fetch_add(1, Ordering::Release);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Reading {
+    id: u64,
+    double: u64,
+    shifted: u64,
+}
+
+impl Reading {
+    fn new(id: u64) -> Self {
+        Reading {
+            id,
+            double: id * 2,
+            shifted: id + 7,
+        }
+    }
+}
+
+impl From<Reading> for [u64; 3] {
+    fn from(r: Reading) -> Self {
+        [r.id, r.double, r.shifted]
+    }
+}
+
+impl From<[u64; 3]> for Reading {
+    fn from([id, double, shifted]: [u64; 3]) -> Self {
+        Reading {
+            id,
+            double,
+            shifted,
+        }
+    }
+}
+
+fn seqlock_example() {
+    let lock: SeqLock<Reading, 3> = SeqLock::new(Reading::new(0));
+    lock.write(Reading::new(42));
+    let (reading, retries) = lock.read();
+    println!(
+        "seqlock: read {} {} {} with {retries} retries",
+        reading.id, reading.double, reading.shifted
+    );
+}
+
+// EPOCH-BASED RECLAMATION
+//
+// The idea behind crossbeam-epoch, reduced to the bookkeeping. There is a global epoch counter
+// and each participant thread has a local slot: enter() copies the global epoch into it and
+// the guard returned clears it when dropped (INACTIVE). A node removed from a shared structure
+// at epoch e is "retired", not freed, because threads that entered before may still read it.
+// It can be freed when every active thread has entered after e: those threads started after
+// the removal and can't have found the node. Inactive threads don't hold anything.
+const INACTIVE: usize = usize::MAX;
+
+struct EpochDomain {
+    global: AtomicUsize,
+    locals: Vec<AtomicUsize>,
+}
+
+struct EpochGuard<'a> {
+    slot: &'a AtomicUsize,
+}
+
+impl EpochDomain {
+    fn new(participants: usize) -> Self {
+        EpochDomain {
+            global: AtomicUsize::new(0),
+            locals: (0..participants)
+                .map(|_| AtomicUsize::new(INACTIVE))
+                .collect(),
+        }
+    }
+
+    fn current(&self) -> usize {
+        self.global.load(Ordering::SeqCst)
+    }
+
+    fn advance(&self) -> usize {
+        self.global.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    fn enter(&self, participant: usize) -> EpochGuard<'_> {
+        let slot = &self.locals[participant];
+        slot.store(self.current(), Ordering::SeqCst);
+        EpochGuard { slot }
+    }
+
+    fn can_reclaim(&self, retired_epoch: usize) -> bool {
+        self.locals.iter().all(|local| {
+            let epoch = local.load(Ordering::SeqCst);
+            epoch == INACTIVE || epoch > retired_epoch
+        })
+    }
+}
+
+impl Drop for EpochGuard<'_> {
+    fn drop(&mut self) {
+        self.slot.store(INACTIVE, Ordering::SeqCst);
+    }
+}
+
+fn epoch_example() {
+    let domain = EpochDomain::new(3);
+    let reader = domain.enter(0);
+    let retired = domain.current();
+    domain.advance();
+    println!(
+        "epoch reclamation: node retired at epoch {retired}, reclaimable with a reader inside: {}",
+        domain.can_reclaim(retired)
+    );
+    drop(reader);
+    println!(
+        "epoch reclamation: reclaimable after the reader left: {}",
+        domain.can_reclaim(retired)
+    );
+}
+
+// STD RWLOCK AND PARKING_LOT RWLOCK
+//
+// The same read-heavy cache (a HashMap behind a RwLock, 99 reads every write) built on two
+// locks. parking_lot::RwLock is smaller, its guards are returned without a Result because it
+// has no poisoning, and it is eventually fair: a waiting writer stops new readers from
+// entering, so writers can't starve under a continuous stream of reads.
+// Both caches are prefilled and every write stores the value already there, so the checksum of
+// the reads doesn't depend on the scheduling and the two must give the same result.
+// The caches are in the rwlock_cache module of the library, shared with the criterion benchmark
+// in benches/rwlock_cache.rs that compares their throughput.
+// Run with: cargo run --bin 12-concurrency --features parking_lot
+//           cargo bench --features parking_lot --bench rwlock_cache
+#[cfg(feature = "parking_lot")]
+fn rwlock_cache_example() {
+    let std_cache = StdCache(RwLock::new(prefilled()));
+    let parking_cache = ParkingLotCache(parking_lot::RwLock::new(prefilled()));
+    println!(
+        "rwlock cache: checksum {} with std::sync::RwLock, {} with parking_lot::RwLock",
+        read_heavy_workload(&std_cache, 4, 1000),
+        read_heavy_workload(&parking_cache, 4, 1000)
+    );
+}
+
+// WAIT WITH A TIMEOUT
+//
+// wait_timeout_while() is wait_while() with a limit: it returns when the condition becomes
+// false or when the timeout expires, whichever comes first, together with a WaitTimeoutResult
+// telling which one happened. The value behind the guard is checked anyway because the
+// condition may have become true exactly while the timeout was expiring.
+fn wait_for_signal(pair: Arc<(Mutex<bool>, Condvar)>, timeout: Duration) -> bool {
+    let (lock, cvar) = &*pair;
+    let (signalled, _result) = cvar
+        .wait_timeout_while(lock.lock().unwrap(), timeout, |signalled| !*signalled)
+        .unwrap();
+    *signalled
+}
+
+fn wait_timeout_example() {
+    let silent = Arc::new((Mutex::new(false), Condvar::new()));
+    let signalled = wait_for_signal(silent, Duration::from_millis(50));
+    println!("wait timeout: signalled {signalled} after 50ms without any notify");
+}
+
+// FALSE NOTIFICATIONS, REPRODUCED
+//
+// The waiter records under the lock that it is about to wait, so once the main thread can take
+// the lock and sees the flag the waiter is certainly sleeping inside wait(). Then a notify_all()
+// is sent without changing the condition, playing the role of a false notification.
+// naive_wait() calls wait() once and trusts it: it wakes up and proceeds with `ready` still
+// false. robust_wait() uses wait_while(): it checks `ready` again, goes back to sleep and
+// proceeds only after the real signal.
+struct Signal {
+    state: Mutex<(bool, bool)>, // (ready, waiting)
+    cvar: Condvar,
+}
+
+impl Signal {
+    fn new() -> Self {
+        Signal {
+            state: Mutex::new((false, false)),
+            cvar: Condvar::new(),
+        }
+    }
+
+    fn naive_wait(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.1 = true;
+        if !state.0 {
+            state = self.cvar.wait(state).unwrap();
+        }
+        state.0
+    }
+
+    fn robust_wait(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.1 = true;
+        state = self.cvar.wait_while(state, |(ready, _)| !*ready).unwrap();
+        state.0
+    }
+
+    fn wait_until_waiting(&self) {
+        while !self.state.lock().unwrap().1 {
+            thread::yield_now();
+        }
+    }
+
+    fn false_notify(&self) {
+        let _state = self.state.lock().unwrap();
+        self.cvar.notify_all();
+    }
+
+    fn signal(&self) {
+        self.state.lock().unwrap().0 = true;
+        self.cvar.notify_all();
+    }
+}
+
+fn false_wakeup_example() {
+    let naive = Signal::new();
+    let robust = Signal::new();
+    let (naive_condition, robust_condition) = thread::scope(|s| {
+        let naive_waiter = s.spawn(|| naive.naive_wait());
+        let robust_waiter = s.spawn(|| robust.robust_wait());
+        naive.wait_until_waiting();
+        robust.wait_until_waiting();
+        naive.false_notify();
+        robust.false_notify();
+        robust.signal();
+        (naive_waiter.join().unwrap(), robust_waiter.join().unwrap())
+    });
+    println!(
+        "false wakeup: wait() returned with the condition {naive_condition}, wait_while() with {robust_condition}"
+    );
+}
+
+// LOST NOTIFICATIONS, REPRODUCED
+//
+// The producer sends 20 notifications as fast as it can, the consumer needs some milliseconds
+// for each one.
+// lossy_consumer() handles one message for each notification it is woken up by: while it is
+// busy with one the next notify_one() finds nobody waiting on the condvar and is lost, and so is
+// the message, it is never handled.
+// counted_consumer() is the fix described above: the producer also increments a counter of
+// pending notifications under the lock and the consumer, after each critical task, checks the
+// counter (wait_while) before going back to sleep, so the ones that arrived meanwhile are
+// handled without waiting for a notification.
+struct Mailbox {
+    state: Mutex<(usize, bool)>, // (pending, done)
+    cvar: Condvar,
+}
+
+impl Mailbox {
+    fn new() -> Self {
+        Mailbox {
+            state: Mutex::new((0, false)),
+            cvar: Condvar::new(),
+        }
+    }
+
+    fn produce(&self, notifications: usize) {
+        for _ in 0..notifications {
+            self.state.lock().unwrap().0 += 1;
+            self.cvar.notify_one();
+        }
+        self.state.lock().unwrap().1 = true;
+        self.cvar.notify_one();
+    }
+
+    fn lossy_consumer(&self, work: Duration) -> usize {
+        let mut handled = 0;
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let (mut guard, result) = self.cvar.wait_timeout(state, work * 10).unwrap();
+            if result.timed_out() && guard.1 {
+                return handled;
+            }
+            if guard.0 == 0 {
+                state = guard;
+                continue;
+            }
+            guard.0 -= 1;
+            drop(guard);
+            handled += 1;
+            thread::sleep(work);
+            state = self.state.lock().unwrap();
+        }
+    }
+
+    fn counted_consumer(&self, work: Duration) -> usize {
+        let mut handled = 0;
+        loop {
+            let state = self.state.lock().unwrap();
+            let mut state = self
+                .cvar
+                .wait_while(state, |(pending, done)| *pending == 0 && !*done)
+                .unwrap();
+            if state.0 == 0 {
+                return handled;
+            }
+            state.0 -= 1;
+            drop(state);
+            handled += 1;
+            thread::sleep(work);
+        }
+    }
+}
+
+fn lost_notification_example() {
+    const SENT: usize = 20;
+    let work = Duration::from_millis(5);
+
+    let mailbox = Mailbox::new();
+    let lossy = thread::scope(|s| {
+        let consumer = s.spawn(|| mailbox.lossy_consumer(work));
+        thread::sleep(work);
+        mailbox.produce(SENT);
+        consumer.join().unwrap()
+    });
+
+    let mailbox = Mailbox::new();
+    let counted = thread::scope(|s| {
+        let consumer = s.spawn(|| mailbox.counted_consumer(work));
+        thread::sleep(work);
+        mailbox.produce(SENT);
+        consumer.join().unwrap()
+    });
+    println!(
+        "lost notifications: {lossy} of {SENT} handled by waiting only, {counted} with the counter"
+    );
+}
+
+// TWO-STAGE PIPELINE
+//
+// Each stage runs in its own scoped thread and the stages are connected by channels: while the
+// second stage works on an item, the first is already working on the next one. Channels are
+// FIFO, so the outputs come out in the same order as the inputs.
+// Being scoped threads the stage closures can borrow data of the caller (here a lookup table),
+// they only need to be Sync because they are used from another thread.
+fn run_staged<I, A, B>(
+    inputs: Vec<I>,
+    stage1: impl Fn(I) -> A + Sync,
+    stage2: impl Fn(A) -> B + Sync,
+) -> Vec<B>
+where
+    I: Send,
+    A: Send,
+    B: Send,
+{
+    let (to_stage2, from_stage1) = mpsc::channel();
+    let (to_output, outputs) = mpsc::channel();
+    let (stage1, stage2) = (&stage1, &stage2);
+    thread::scope(|s| {
+        // The senders are moved in the threads: when a stage ends its sender is dropped, the
+        // channel is closed and the loop of the next stage ends too.
+        s.spawn(move || {
+            for input in inputs {
+                to_stage2.send(stage1(input)).unwrap();
+            }
+        });
+        s.spawn(move || {
+            for a in from_stage1 {
+                to_output.send(stage2(a)).unwrap();
+            }
+        });
+    });
+    outputs.into_iter().collect()
+}
+
+fn pipeline_example() {
+    let names = ["zero", "one", "two", "three"];
+    let staged = run_staged(
+        vec![3, 1, 2],
+        |i| names[i].to_uppercase(),
+        |name| format!("{name}:{}", name.len()),
+    );
+    println!("pipeline: [3, 1, 2] through both stages gives {staged:?}");
+}
+
+// FAIR READ/WRITE LOCK
+//
+// With a lock that lets readers in whenever no writer holds it, a writer can wait forever if
+// the readers keep overlapping each other (writer starvation). FairRwLock counts the writers
+// that are waiting and, as soon as there is one, new readers wait as well: the readers
+// already inside finish and then the writer gets the lock.
+// The state is a Mutex guarding the counters and a single Condvar, every release wakes all
+// the waiting threads and each one checks again its own condition with wait_while().
+struct RwState {
+    readers: usize,
+    writer: bool,
+    waiting_writers: usize,
+}
+
+struct FairRwLock<T> {
+    state: Mutex<RwState>,
+    cvar: Condvar,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send + Sync> Sync for FairRwLock<T> {}
+
+struct FairReadGuard<'a, T> {
+    lock: &'a FairRwLock<T>,
+}
+
+struct FairWriteGuard<'a, T> {
+    lock: &'a FairRwLock<T>,
+}
+
+impl<T> FairRwLock<T> {
+    fn new(value: T) -> Self {
+        FairRwLock {
+            state: Mutex::new(RwState {
+                readers: 0,
+                writer: false,
+                waiting_writers: 0,
+            }),
+            cvar: Condvar::new(),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    fn read(&self) -> FairReadGuard<'_, T> {
+        let state = self.state.lock().unwrap();
+        let mut state = self
+            .cvar
+            .wait_while(state, |s| s.writer || s.waiting_writers > 0)
+            .unwrap();
+        state.readers += 1;
+        FairReadGuard { lock: self }
+    }
+
+    fn write(&self) -> FairWriteGuard<'_, T> {
+        let mut state = self.state.lock().unwrap();
+        state.waiting_writers += 1;
+        let mut state = self
+            .cvar
+            .wait_while(state, |s| s.writer || s.readers > 0)
+            .unwrap();
+        state.waiting_writers -= 1;
+        state.writer = true;
+        FairWriteGuard { lock: self }
+    }
+}
+
+impl<T> Deref for FairReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for FairReadGuard<'_, T> {
+    fn drop(&mut self) {
+        let mut state = self.lock.state.lock().unwrap();
+        state.readers -= 1;
+        if state.readers == 0 {
+            self.lock.cvar.notify_all();
+        }
+    }
+}
+
+impl<T> Deref for FairWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for FairWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for FairWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.state.lock().unwrap().writer = false;
+        self.lock.cvar.notify_all();
+    }
+}
+
+// Four readers keep the lock busy without pauses, each read overlapping the others. While the
+// writer waits only the readers that were already entering can get in, so the number of reads
+// between the request and the write is bounded by the number of readers (twice, because
+// of the reads already inside when the counter is loaded).
+fn fair_rwlock_example() {
+    const READERS: usize = 4;
+    let lock = FairRwLock::new(0u64);
+    let reads = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let overtaken = thread::scope(|s| {
+        for _ in 0..READERS {
+            s.spawn(|| {
+                while !stop.load(Ordering::SeqCst) {
+                    let _value = lock.read();
+                    reads.fetch_add(1, Ordering::SeqCst);
+                    thread::sleep(Duration::from_micros(200));
+                }
+            });
+        }
+        let mut overtaken = Vec::new();
+        for _ in 0..5 {
+            thread::sleep(Duration::from_millis(5));
+            let before = reads.load(Ordering::SeqCst);
+            let mut value = lock.write();
+            overtaken.push(reads.load(Ordering::SeqCst) - before);
+            *value += 1;
+        }
+        stop.store(true, Ordering::SeqCst);
+        overtaken
+    });
+    println!("fair rwlock: reads overtaking each writer {overtaken:?}");
+}
+
+fn main() {
+    semaphore_example();
+    latch_example();
+    countdown_latch_example();
+    thread_local_example();
+    map_reduce_example();
+    pool_example();
+    broadcaster_example();
+    #[cfg(feature = "crossbeam")]
+    treiber_stack_example();
+    seqlock_example();
+    epoch_example();
+    #[cfg(feature = "parking_lot")]
+    rwlock_cache_example();
+    wait_timeout_example();
+    false_wakeup_example();
+    lost_notification_example();
+    pipeline_example();
+    fair_rwlock_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "parking_lot")]
+    use esercizi_malnati::rwlock_cache::CACHE_KEYS;
+    use std::sync::Barrier;
+    use std::time::Instant;
+
+    #[test]
+    fn semaphore() {
+        assert!(max_inside_semaphore(3) <= 3);
+        assert_eq!(max_inside_semaphore(1), 1);
+
+        // With no permits left a thread blocks on acquire() and proceeds only after a release().
+        let semaphore = Arc::new(Semaphore::new(0));
+        let passed = Arc::new(AtomicUsize::new(0));
+        let waiter = {
+            let semaphore = Arc::clone(&semaphore);
+            let passed = Arc::clone(&passed);
+            thread::spawn(move || {
+                semaphore.acquire();
+                passed.store(1, Ordering::SeqCst);
+            })
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(passed.load(Ordering::SeqCst), 0);
+        semaphore.release();
+        waiter.join().unwrap();
+        assert_eq!(passed.load(Ordering::SeqCst), 1);
+    }
+
+    // Several waiters block on the latch, each one records the instant it got released and all
+    // of them have to come after the instant in which signal() was called.
+    #[test]
+    fn latch() {
+        let latch = Arc::new(Latch::new());
+        let waiters: Vec<_> = (0..4)
+            .map(|_| {
+                let latch = Arc::clone(&latch);
+                thread::spawn(move || {
+                    latch.wait();
+                    Instant::now()
+                })
+            })
+            .collect();
+
+        thread::sleep(Duration::from_millis(50));
+        let signaled_at = Instant::now();
+        latch.signal();
+
+        for waiter in waiters {
+            assert!(waiter.join().unwrap() >= signaled_at);
+        }
+        // Once open the latch doesn't block anymore.
+        latch.wait();
+    }
+
+    // Each worker marks itself as finished before counting down, so when the coordinator wakes
+    // up every worker has to be already finished.
+    #[test]
+    fn countdown_latch() {
+        const WORKERS: usize = 5;
+        let latch = Arc::new(CountdownLatch::new(WORKERS));
+        let finished = Arc::new(AtomicUsize::new(0));
+
+        let coordinator = {
+            let latch = Arc::clone(&latch);
+            let finished = Arc::clone(&finished);
+            thread::spawn(move || {
+                latch.wait();
+                finished.load(Ordering::SeqCst)
+            })
+        };
+
+        let workers: Vec<_> = (0..WORKERS)
+            .map(|i| {
+                let latch = Arc::clone(&latch);
+                let finished = Arc::clone(&finished);
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(10 * i as u64));
+                    finished.fetch_add(1, Ordering::SeqCst);
+                    latch.count_down();
+                })
+            })
+            .collect();
+
+        assert_eq!(coordinator.join().unwrap(), WORKERS);
+        for worker in workers {
+            worker.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn thread_local() {
+        const THREADS: usize = 4;
+        let data: Vec<u64> = (1..=1000).collect();
+        let merges = AtomicUsize::new(0);
+        let (local_sizes, merged) = square_in_threads(&data, THREADS, &merges);
+
+        // Each thread filled its own buffer with its own chunk only and merged it exactly once.
+        assert_eq!(local_sizes, vec![data.len() / THREADS; THREADS]);
+        assert_eq!(merges.load(Ordering::SeqCst), THREADS);
+        // The buffer of the main thread was never touched by the workers.
+        assert!(LOCAL_BUFFER.with(|buffer| buffer.borrow().is_empty()));
+
+        let total: u64 = merged.iter().sum();
+        let expected: u64 = data.iter().map(|x| x * x).sum();
+        assert_eq!(total, expected);
+    }
+
+    #[test]
+    fn map_reduce_works() {
+        let data: Vec<u64> = (1..=100_000).collect();
+        let sum_of_squares = map_reduce(data.clone(), 8, |x| x * x, |a, b| a + b, 0);
+        assert_eq!(sum_of_squares, data.iter().map(|x| x * x).sum::<u64>());
+
+        let values: Vec<i64> = (0..50_000).map(|i| (i * 7919) % 10_007 - 5_000).collect();
+        let max = map_reduce(values.clone(), 3, |x| x, i64::max, i64::MIN);
+        assert_eq!(max, *values.iter().max().unwrap());
+
+        // An empty input returns the identity.
+        assert_eq!(map_reduce(Vec::<u64>::new(), 4, |x| x, |a, b| a + b, 0), 0);
+    }
+
+    // Ten threads share three buffers, each one counts the buffers checked out while it holds
+    // one and writes in the buffer how many times it has been used.
+    #[test]
+    fn pool() {
+        let pool = Pool::new(vec![Vec::<usize>::new(), Vec::new(), Vec::new()]);
+        let checked_out = Arc::new(AtomicUsize::new(0));
+        let max_checked_out = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..10)
+            .map(|id| {
+                let pool = pool.clone();
+                let checked_out = Arc::clone(&checked_out);
+                let max_checked_out = Arc::clone(&max_checked_out);
+                thread::spawn(move || {
+                    for _ in 0..5 {
+                        let mut buffer = pool.acquire();
+                        let now = checked_out.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_checked_out.fetch_max(now, Ordering::SeqCst);
+                        buffer.push(id);
+                        thread::sleep(Duration::from_millis(1));
+                        checked_out.fetch_sub(1, Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert!(max_checked_out.load(Ordering::SeqCst) <= 3);
+        assert_eq!(pool.available(), 3);
+        // All three buffers held together: every use has been recorded in one of them.
+        let guards: Vec<_> = (0..3).map(|_| pool.acquire()).collect();
+        let total: usize = guards.iter().map(|g| g.len()).sum();
+        assert_eq!(total, 50);
+    }
+
+    // The producer publishes the numbers 1..=2000, value and version are always equal. Each
+    // consumer polls until it sees the last one, checking that the versions it observes only
+    // grow.
+    #[test]
+    fn broadcaster() {
+        const LAST: u64 = 2000;
+        let broadcaster = Broadcaster::new(0u64);
+        let observed = thread::scope(|s| {
+            let consumers: Vec<_> = (0..4)
+                .map(|_| {
+                    s.spawn(|| {
+                        let (mut seen, mut updates) = (0, 0);
+                        while seen != LAST {
+                            match broadcaster.latest(seen) {
+                                Some((version, value)) => {
+                                    assert_eq!(version, value);
+                                    assert!(version > seen);
+                                    seen = version;
+                                    updates += 1;
+                                }
+                                None => thread::yield_now(),
+                            }
+                        }
+                        updates
+                    })
+                })
+                .collect();
+            for value in 1..=LAST {
+                broadcaster.publish(value);
+            }
+            consumers
+                .into_iter()
+                .map(|c| c.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert!(observed
+            .iter()
+            .all(|&updates| (1..=LAST).contains(&updates)));
+        assert_eq!(broadcaster.latest(LAST), None);
+    }
+
+    // Eight threads push 1000 distinct values each and pop about half of them while the others
+    // are still pushing; popped and remaining values together must be every pushed value exactly
+    // once.
+    #[cfg(feature = "crossbeam")]
+    #[test]
+    fn treiber_stack() {
+        let stack = TreiberStack::new();
+        let mut popped: Vec<u32> = thread::scope(|s| {
+            let workers: Vec<_> = (0..8u32)
+                .map(|t| {
+                    let stack = &stack;
+                    s.spawn(move || {
+                        let mut popped = Vec::new();
+                        for i in 0..1000 {
+                            stack.push(t * 1000 + i);
+                            if i % 2 == 0 {
+                                popped.extend(stack.pop());
+                            }
+                        }
+                        popped
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|w| w.join().unwrap())
+                .collect()
+        });
+        while let Some(value) = stack.pop() {
+            popped.push(value);
+        }
+        popped.sort();
+        assert_eq!(popped, (0..8000).collect::<Vec<_>>());
+    }
+
+    // The fields of a Reading are always consistent with each other, a torn copy would break
+    // the relation. Readers also check that the id never goes backwards.
+    #[test]
+    fn seqlock() {
+        const WRITES: u64 = 20_000;
+        let lock: SeqLock<Reading, 3> = SeqLock::new(Reading::new(0));
+        thread::scope(|s| {
+            let readers: Vec<_> = (0..4)
+                .map(|_| {
+                    s.spawn(|| {
+                        let (mut last, mut retries) = (0, 0);
+                        while last < WRITES {
+                            let (reading, r) = lock.read();
+                            assert_eq!(reading.double, reading.id * 2);
+                            assert_eq!(reading.shifted, reading.id + 7);
+                            assert!(reading.id >= last);
+                            last = reading.id;
+                            retries += r;
+                        }
+                        retries
+                    })
+                })
+                .collect();
+            for id in 1..=WRITES {
+                lock.write(Reading::new(id));
+            }
+            assert!(readers.into_iter().all(|r| r.join().is_ok()));
+        });
+        assert_eq!(lock.read().0.id, WRITES);
+    }
+
+    #[test]
+    fn epoch() {
+        let domain = EpochDomain::new(3);
+        assert!(domain.can_reclaim(0));
+
+        let reader0 = domain.enter(0);
+        let retired = domain.current();
+        domain.advance();
+        // Reader 0 entered at the retirement epoch, it may still hold the node.
+        assert!(!domain.can_reclaim(retired));
+        let reader1 = domain.enter(1);
+        assert!(!domain.can_reclaim(retired));
+        drop(reader0);
+        // Only reader 1 is active and it entered after the retirement.
+        assert!(domain.can_reclaim(retired));
+
+        let retired = domain.current();
+        domain.advance();
+        assert!(!domain.can_reclaim(retired));
+        drop(reader1);
+        assert!(domain.can_reclaim(retired));
+
+        // With real threads: while a thread pinned at the retirement epoch is inside, nothing
+        // retired at that epoch can be freed; once it leaves, reclamation is allowed.
+        let inside = Barrier::new(2);
+        let release = Barrier::new(2);
+        thread::scope(|s| {
+            s.spawn(|| {
+                let _guard = domain.enter(2);
+                inside.wait();
+                release.wait();
+            });
+            inside.wait();
+            let retired = domain.current();
+            domain.advance();
+            assert!(!domain.can_reclaim(retired));
+            release.wait();
+            while !domain.can_reclaim(retired) {
+                thread::yield_now();
+            }
+        });
+    }
+
+    #[cfg(feature = "parking_lot")]
+    #[test]
+    fn rwlock_cache() {
+        let (threads, ops) = (4, 20_000);
+        let expected: u64 = (0..threads)
+            .flat_map(|t| {
+                (0..ops)
+                    .filter(|i| i % 100 != 0)
+                    .map(move |i| (t * ops + i) % CACHE_KEYS)
+            })
+            .map(|k| k * k)
+            .sum();
+
+        let std_cache = StdCache(RwLock::new(prefilled()));
+        assert_eq!(read_heavy_workload(&std_cache, threads, ops), expected);
+        let parking_cache = ParkingLotCache(parking_lot::RwLock::new(prefilled()));
+        assert_eq!(read_heavy_workload(&parking_cache, threads, ops), expected);
+    }
+
+    #[test]
+    fn wait_timeout() {
+        let pair = Arc::new((Mutex::new(false), Condvar::new()));
+        let signaller = Arc::clone(&pair);
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            let (lock, cvar) = &*signaller;
+            *lock.lock().unwrap() = true;
+            cvar.notify_one();
+        });
+        let start = Instant::now();
+        assert!(wait_for_signal(Arc::clone(&pair), Duration::from_secs(5)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        handle.join().unwrap();
+
+        let silent = Arc::new((Mutex::new(false), Condvar::new()));
+        let start = Instant::now();
+        assert!(!wait_for_signal(silent, Duration::from_millis(50)));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn false_wakeup() {
+        let naive = Signal::new();
+        let proceeded_with = thread::scope(|s| {
+            let waiter = s.spawn(|| naive.naive_wait());
+            naive.wait_until_waiting();
+            naive.false_notify();
+            waiter.join().unwrap()
+        });
+        assert!(
+            !proceeded_with,
+            "the naive waiter proceeds without the condition"
+        );
+
+        let robust = Signal::new();
+        let proceeded_with = thread::scope(|s| {
+            let waiter = s.spawn(|| robust.robust_wait());
+            robust.wait_until_waiting();
+            robust.false_notify();
+            thread::sleep(Duration::from_millis(50));
+            assert!(
+                !waiter.is_finished(),
+                "the robust waiter went back to sleep"
+            );
+            robust.signal();
+            waiter.join().unwrap()
+        });
+        assert!(proceeded_with);
+    }
+
+    // All the messages are sent before the consumers start waiting, so every notification finds
+    // nobody on the condvar: the lossy consumer handles none of them while the counted one finds
+    // them all in the counter.
+    #[test]
+    fn lost_notification() {
+        const SENT: usize = 20;
+        let work = Duration::from_millis(1);
+
+        let mailbox = Mailbox::new();
+        mailbox.produce(SENT);
+        assert!(mailbox.lossy_consumer(work) < SENT);
+
+        let mailbox = Mailbox::new();
+        mailbox.produce(SENT);
+        assert_eq!(mailbox.counted_consumer(work), SENT);
+    }
+
+    #[test]
+    fn pipeline() {
+        let names = ["zero", "one", "two", "three", "four", "five"];
+        let inputs: Vec<usize> = (0..60).map(|i| (i * 7) % 6).collect();
+        let staged = run_staged(
+            inputs.clone(),
+            |i| names[i].to_uppercase(),
+            |name| format!("{name}:{}", name.len()),
+        );
+        let sequential: Vec<String> = inputs
+            .iter()
+            .map(|&i| names[i].to_uppercase())
+            .map(|name| format!("{name}:{}", name.len()))
+            .collect();
+        assert_eq!(staged, sequential);
+        assert_eq!(&staged[..3], ["ZERO:4", "ONE:3", "TWO:3"]);
+        assert!(run_staged(Vec::<u8>::new(), |x| x, |x| x).is_empty());
+    }
+
+    // A reader holds the lock while a writer asks for it. Once the writer is counted as waiting,
+    // the readers that arrive later park behind it even if the lock is only held for reading:
+    // when the first reader leaves the writer goes first and every later reader sees its value.
+    #[test]
+    fn fair_rwlock() {
+        const READERS: usize = 4;
+        let lock = FairRwLock::new(0u64);
+        let order = Mutex::new(Vec::new());
+        let first_reader = lock.read();
+        thread::scope(|s| {
+            s.spawn(|| {
+                let mut value = lock.write();
+                *value += 1;
+                order.lock().unwrap().push("writer");
+            });
+            while lock.state.lock().unwrap().waiting_writers == 0 {
+                thread::yield_now();
+            }
+            for _ in 0..READERS {
+                s.spawn(|| {
+                    let value = lock.read();
+                    order.lock().unwrap().push("reader");
+                    assert_eq!(*value, 1);
+                });
+            }
+            drop(first_reader);
+        });
+        let order = order.into_inner().unwrap();
+        assert_eq!(order.len(), READERS + 1);
+        assert_eq!(order[0], "writer");
+    }
+}
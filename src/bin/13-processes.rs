@@ -137,3 +137,718 @@
 //
 // Zbus is a crate to map the specific interprocess communication protocol of Linux.
 //
+// -------------------------------- EXAMPLES -------------------------------------------------------
+//
+// Most of the examples start child processes through sh so they are compiled only on Unix systems.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+#[cfg(feature = "mmap")]
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Cursor, Write};
+#[cfg(feature = "mmap")]
+use std::path::Path;
+#[cfg(unix)]
+use std::process::Child;
+use std::process::{Command, ExitStatus, Stdio};
+#[cfg(feature = "mmap")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(any(unix, feature = "sysinfo"))]
+use std::thread;
+#[cfg(any(unix, feature = "sysinfo"))]
+use std::time::Duration;
+#[cfg(unix)]
+use std::time::Instant;
+
+// STREAMING OUTPUT
+//
+// output() waits for the end of the child and returns everything it printed at once. Taking the
+// piped stdout out of the Child instead gives a reader over the pipe: wrapped in a BufReader it
+// produces each line as soon as the child writes it, while the child is still running.
+// The reader has to be consumed before wait(), a child blocked on a full pipe would never end.
+fn stream_lines(cmd: &mut Command, mut on_line: impl FnMut(&str)) -> io::Result<ExitStatus> {
+    let mut child = cmd.stdout(Stdio::piped()).spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| io::Error::other("stdout not captured"))?;
+    for line in BufReader::new(stdout).lines() {
+        on_line(&line?);
+    }
+    child.wait()
+}
+
+#[cfg(unix)]
+fn stream_lines_example() -> io::Result<()> {
+    let status = stream_lines(
+        Command::new("sh")
+            .arg("-c")
+            .arg("for i in 1 2 3; do echo \"line $i\"; sleep 0.05; done"),
+        |line| println!("stream lines: received {line:?}"),
+    )?;
+    println!("stream lines: child exited with {status}");
+    Ok(())
+}
+
+// RESOURCE USAGE (feature "sysinfo")
+//
+// sysinfo reads the statistics the OS keeps for every process. The CPU usage is a percentage
+// computed between two refreshes of the same System, that's why spawn_and_monitor() keeps one
+// System alive across all the samples while process_stats() (a single reading) reports 0.
+// Run with: cargo run --bin 13-processes --features sysinfo
+#[cfg(feature = "sysinfo")]
+#[derive(Debug)]
+struct ProcessStats {
+    cpu_usage: f32,
+    memory: u64,
+    run_time: Duration,
+}
+
+#[cfg(feature = "sysinfo")]
+fn refresh_stats(system: &mut sysinfo::System, pid: u32) -> Option<ProcessStats> {
+    use sysinfo::{Pid, ProcessesToUpdate};
+
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).map(|process| ProcessStats {
+        cpu_usage: process.cpu_usage(),
+        memory: process.memory(),
+        run_time: Duration::from_secs(process.run_time()),
+    })
+}
+
+#[cfg(feature = "sysinfo")]
+fn process_stats(pid: u32) -> Option<ProcessStats> {
+    refresh_stats(&mut sysinfo::System::new(), pid)
+}
+
+// Samples the child every `interval` while it is running, at most `samples` times, then waits
+// for it to end.
+#[cfg(feature = "sysinfo")]
+fn spawn_and_monitor(
+    cmd: &mut Command,
+    interval: Duration,
+    samples: usize,
+) -> io::Result<Vec<ProcessStats>> {
+    let mut child = cmd.spawn()?;
+    let mut system = sysinfo::System::new();
+    let mut stats = Vec::new();
+    for _ in 0..samples {
+        if child.try_wait()?.is_some() {
+            break;
+        }
+        if let Some(sample) = refresh_stats(&mut system, child.id()) {
+            stats.push(sample);
+        }
+        thread::sleep(interval);
+    }
+    child.wait()?;
+    Ok(stats)
+}
+
+#[cfg(feature = "sysinfo")]
+fn process_stats_example() -> io::Result<()> {
+    let own = process_stats(std::process::id()).expect("the current process exists");
+    let stats = spawn_and_monitor(
+        Command::new("sh").arg("-c").arg("sleep 0.3"),
+        Duration::from_millis(50),
+        4,
+    )?;
+    println!(
+        "process stats: {} samples of the child, last {:?} (own cpu {}%, {} bytes, run time {:?})",
+        stats.len(),
+        stats.last(),
+        own.cpu_usage,
+        own.memory,
+        own.run_time
+    );
+    Ok(())
+}
+
+// SHARED MEMORY COUNTER (feature "mmap")
+//
+// Mapping the same file in the address space of two processes gives them a piece of memory in
+// common: what one process writes in it is immediately visible to the other, without passing
+// through read() and write() calls. Memory shared this way has no synchronization at all, so the
+// 8 bytes of the counter are accessed only through an AtomicU64, whose read-modify-write
+// operations are atomic also across processes.
+// The child processes are this same executable started again with a special first argument.
+// Run with: cargo run --bin 13-processes --features mmap
+#[cfg(feature = "mmap")]
+struct SharedCounter {
+    map: memmap2::MmapRaw,
+}
+
+#[cfg(feature = "mmap")]
+impl SharedCounter {
+    fn counter(&self) -> &AtomicU64 {
+        // SAFETY: the mapping is at least 8 bytes long, page aligned, readable and writable and
+        // it lives as long as self. Every process accesses these bytes only atomically.
+        unsafe { AtomicU64::from_ptr(self.map.as_mut_ptr().cast::<u64>()) }
+    }
+
+    fn increment(&self) -> u64 {
+        self.counter().fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    fn value(&self) -> u64 {
+        self.counter().load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn open_shared_counter(path: &Path) -> io::Result<SharedCounter> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    if file.metadata()?.len() < 8 {
+        // A new file is extended with zeros, so the counter starts from 0.
+        file.set_len(8)?;
+    }
+    Ok(SharedCounter {
+        map: memmap2::MmapRaw::map_raw(&file)?,
+    })
+}
+
+#[cfg(feature = "mmap")]
+const INCREMENT_CHILD: &str = "--increment-shared-counter";
+
+// Entry point of the children: `13-processes --increment-shared-counter <path> <times>`.
+#[cfg(feature = "mmap")]
+fn increment_child(mut args: impl Iterator<Item = String>) -> io::Result<()> {
+    let path = args.next().expect("missing counter path");
+    let times: u64 = args
+        .next()
+        .and_then(|times| times.parse().ok())
+        .expect("missing number of increments");
+    let counter = open_shared_counter(Path::new(&path))?;
+    for _ in 0..times {
+        counter.increment();
+    }
+    Ok(())
+}
+
+// Starts two children built by `child`, each one incrementing the counter in `path` `times` times
+// while this process does the same, and returns the final value of the counter.
+#[cfg(feature = "mmap")]
+fn count_in_three_processes(
+    path: &Path,
+    times: u64,
+    child: impl Fn() -> io::Result<Command>,
+) -> io::Result<u64> {
+    let _ = std::fs::remove_file(path);
+    let counter = open_shared_counter(path)?;
+
+    let children: Vec<_> = (0..2)
+        .map(|_| child()?.spawn())
+        .collect::<io::Result<_>>()?;
+    // The parent increments the same counter while the children are running.
+    for _ in 0..times {
+        counter.increment();
+    }
+    for mut child in children {
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("child failed with {status}")));
+        }
+    }
+
+    let value = counter.value();
+    drop(counter);
+    std::fs::remove_file(path)?;
+    Ok(value)
+}
+
+#[cfg(feature = "mmap")]
+fn shared_counter_example() -> io::Result<()> {
+    const TIMES: u64 = 10_000;
+    let path = std::env::temp_dir().join(format!("shared_counter_{}", std::process::id()));
+    let value = count_in_three_processes(&path, TIMES, || {
+        let mut child = Command::new(std::env::current_exe()?);
+        child.arg(INCREMENT_CHILD).arg(&path).arg(TIMES.to_string());
+        Ok(child)
+    })?;
+    println!("shared counter: 3 processes counted to {value}");
+    Ok(())
+}
+
+// NAMED PIPES ON WINDOWS (feature "interprocess")
+//
+// A named pipe is a pipe with a name in the \\.\pipe\ namespace, any process knowing the name can
+// connect to the process that created it. interprocess wraps the Win32 API (CreateNamedPipe,
+// ConnectNamedPipe, ...) behind types implementing Read and Write.
+// A byte pipe has no message boundaries, so both sides prefix every message with its length as
+// a little endian u32. The server is an echo: it answers with the same bytes it received.
+// The length comes from the other process, so it is checked against a limit before allocating
+// the buffer, and a client sending garbage or disconnecting only ends its own connection.
+// Run (on Windows) with: cargo run --bin 13-processes --features interprocess
+#[cfg(all(windows, feature = "interprocess"))]
+fn pipe_path(name: &str) -> String {
+    format!(r"\\.\pipe\{}", name)
+}
+
+#[cfg(all(windows, feature = "interprocess"))]
+const MAX_MESSAGE_LEN: usize = 1 << 20;
+
+#[cfg(all(windows, feature = "interprocess"))]
+fn read_message(stream: &mut impl std::io::Read) -> io::Result<Vec<u8>> {
+    let mut length = [0; 4];
+    stream.read_exact(&mut length)?;
+    let length = u32::from_le_bytes(length) as usize;
+    if length > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {length} bytes, the limit is {MAX_MESSAGE_LEN}"),
+        ));
+    }
+    let mut message = vec![0; length];
+    stream.read_exact(&mut message)?;
+    Ok(message)
+}
+
+#[cfg(all(windows, feature = "interprocess"))]
+fn write_message(stream: &mut impl std::io::Write, message: &[u8]) -> io::Result<()> {
+    stream.write_all(&(message.len() as u32).to_le_bytes())?;
+    stream.write_all(message)?;
+    stream.flush()
+}
+
+// Serves the clients one after the other, forever. Only failing to create the pipe is returned,
+// an error on a connection is logged and the server goes on with the next client.
+#[cfg(all(windows, feature = "interprocess"))]
+fn serve_named_pipe(name: &str) -> io::Result<()> {
+    use interprocess::os::windows::named_pipe::{pipe_mode, PipeListenerOptions};
+
+    let path = pipe_path(name);
+    let listener = PipeListenerOptions::new()
+        .path(std::path::Path::new(&path))
+        .create_duplex::<pipe_mode::Bytes>()?;
+    for connection in listener.incoming() {
+        let echo = connection.and_then(|mut connection| {
+            let request = read_message(&mut connection)?;
+            write_message(&mut connection, &request)
+        });
+        if let Err(e) = echo {
+            eprintln!("named pipe: connection failed: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(windows, feature = "interprocess"))]
+fn query_named_pipe(name: &str, req: &[u8]) -> io::Result<Vec<u8>> {
+    use interprocess::os::windows::named_pipe::{pipe_mode, DuplexPipeStream};
+
+    let mut connection =
+        DuplexPipeStream::<pipe_mode::Bytes>::connect_by_path(pipe_path(name).as_str())?;
+    write_message(&mut connection, req)?;
+    read_message(&mut connection)
+}
+
+#[cfg(all(windows, feature = "interprocess"))]
+fn named_pipe_example() -> io::Result<()> {
+    let name = format!("rust_learning_echo_{}", std::process::id());
+    {
+        let name = name.clone();
+        // The server thread is never joined, it ends with the process.
+        std::thread::spawn(move || serve_named_pipe(&name));
+    }
+
+    // Connecting fails until the server has created the pipe, so the first query is retried.
+    let mut response = Err(io::Error::from(io::ErrorKind::NotFound));
+    for _ in 0..50 {
+        response = query_named_pipe(&name, b"ping");
+        if response.is_ok() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let response = response?;
+    println!(
+        "named pipe: echo server answered {:?}",
+        String::from_utf8_lossy(&response)
+    );
+    Ok(())
+}
+
+// GRACEFUL TERMINATION
+//
+// kill() on a Child ends the process immediately (SIGKILL on Unix): it can't save its state,
+// remove temporary files or tell its own children to stop. The polite way is to send SIGTERM
+// first, which the process can catch to clean up and exit, and to use kill() only if it is
+// still alive after a grace period.
+// The standard library has no API to send a specific signal, so the kill() syscall is called
+// through the libc crate.
+#[cfg(unix)]
+const TERMINATION_GRACE: Duration = Duration::from_secs(2);
+
+#[cfg(unix)]
+fn wait_until(child: &mut Child, deadline: Instant) -> io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(unix)]
+fn run_forwarding_sigterm(cmd: &mut Command, run_for: Duration) -> io::Result<ExitStatus> {
+    let mut child = cmd.spawn()?;
+    if let Some(status) = wait_until(&mut child, Instant::now() + run_for)? {
+        return Ok(status);
+    }
+
+    let pid = libc::pid_t::try_from(child.id()).map_err(io::Error::other)?;
+    // SAFETY: kill() has no memory safety requirements, the pid is the one of our child that
+    // hasn't been waited yet so it can't have been reused by another process.
+    if unsafe { libc::kill(pid, libc::SIGTERM) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    if let Some(status) = wait_until(&mut child, Instant::now() + TERMINATION_GRACE)? {
+        return Ok(status);
+    }
+
+    child.kill()?;
+    child.wait()
+}
+
+#[cfg(unix)]
+fn graceful_termination_example() -> io::Result<()> {
+    // The script catches SIGTERM and exits cleanly with code 0.
+    let status = run_forwarding_sigterm(
+        Command::new("sh")
+            .arg("-c")
+            .arg("trap 'echo cleaning up; exit 0' TERM; while true; do sleep 0.05; done"),
+        Duration::from_millis(200),
+    )?;
+    println!("graceful termination: {status} after SIGTERM");
+    Ok(())
+}
+
+// JSON-RPC OVER STDIO
+//
+// A common way for a parent process to use a helper process is to talk with it through its
+// stdin and stdout: the parent writes a request per line and the child answers with a response
+// per line (language servers work like this). serve_rpc() is the child side, it's generic over
+// BufRead and Write so the same code works with the real Stdin/Stdout or, as in the example,
+// with in-memory Cursors.
+// Requests and responses are structs deriving Deserialize and Serialize, serde_json turns them
+// from/into a line of JSON. Unknown methods and malformed lines get an error response instead of
+// stopping the server, the error codes are the ones defined by JSON-RPC 2.0.
+#[derive(Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct RpcResponse {
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+type Handler = Box<dyn Fn(&Value) -> Result<Value, String>>;
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+fn handle_request(line: &str, handlers: &HashMap<String, Handler>) -> RpcResponse {
+    let failure = |id, code, message: String| RpcResponse {
+        id,
+        result: None,
+        error: Some(RpcError { code, message }),
+    };
+    let request: RpcRequest = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return failure(Value::Null, PARSE_ERROR, e.to_string()),
+    };
+    match handlers.get(&request.method) {
+        None => failure(
+            request.id,
+            METHOD_NOT_FOUND,
+            format!("unknown method {}", request.method),
+        ),
+        Some(handler) => match handler(&request.params) {
+            Ok(result) => RpcResponse {
+                id: request.id,
+                result: Some(result),
+                error: None,
+            },
+            Err(message) => failure(request.id, INVALID_PARAMS, message),
+        },
+    }
+}
+
+fn serve_rpc<R: BufRead, W: Write>(
+    input: R,
+    mut output: W,
+    handlers: &HashMap<String, Handler>,
+) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = handle_request(&line, handlers);
+        serde_json::to_writer(&mut output, &response)?;
+        output.write_all(b"\n")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+fn rpc_handlers() -> HashMap<String, Handler> {
+    let mut handlers: HashMap<String, Handler> = HashMap::new();
+    handlers.insert(
+        "sum".to_string(),
+        Box::new(|params| {
+            let numbers = params.as_array().ok_or("params must be an array")?;
+            numbers
+                .iter()
+                .try_fold(0i64, |sum, n| {
+                    let n = n.as_i64().ok_or("params must be integers")?;
+                    sum.checked_add(n).ok_or("the sum overflows an i64")
+                })
+                .map(Value::from)
+                .map_err(String::from)
+        }),
+    );
+    handlers.insert("echo".to_string(), Box::new(|params| Ok(params.clone())));
+    handlers
+}
+
+fn rpc_example() -> io::Result<()> {
+    let requests = concat!(
+        r#"{"id": 1, "method": "sum", "params": [1, 2, 3]}"#,
+        "\n",
+        r#"{"id": 2, "method": "divide", "params": [1, 0]}"#,
+        "\n",
+    );
+    let mut output = Vec::new();
+    serve_rpc(Cursor::new(requests), &mut output, &rpc_handlers())?;
+    print!("json rpc:\n{}", String::from_utf8_lossy(&output));
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    #[cfg(feature = "mmap")]
+    {
+        let mut args = std::env::args().skip(1);
+        if args.next().as_deref() == Some(INCREMENT_CHILD) {
+            return increment_child(args);
+        }
+    }
+
+    #[cfg(unix)]
+    stream_lines_example()?;
+    #[cfg(feature = "sysinfo")]
+    process_stats_example()?;
+    #[cfg(feature = "mmap")]
+    shared_counter_example()?;
+    #[cfg(all(windows, feature = "interprocess"))]
+    named_pipe_example()?;
+    #[cfg(unix)]
+    graceful_termination_example()?;
+    rpc_example()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[cfg(unix)]
+    #[test]
+    fn stream_lines_in_order() -> io::Result<()> {
+        let mut lines = Vec::new();
+        let status = stream_lines(
+            Command::new("sh")
+                .arg("-c")
+                .arg("for i in 1 2 3; do echo \"line $i\"; sleep 0.05; done"),
+            |line| lines.push(line.to_string()),
+        )?;
+        assert!(status.success());
+        assert_eq!(lines, vec!["line 1", "line 2", "line 3"]);
+        Ok(())
+    }
+
+    #[cfg(feature = "sysinfo")]
+    #[test]
+    fn process_stats_sampled() -> io::Result<()> {
+        let own = process_stats(std::process::id()).expect("the current process exists");
+        assert!(own.memory > 0);
+
+        let stats = spawn_and_monitor(
+            Command::new("sh").arg("-c").arg("sleep 0.3"),
+            Duration::from_millis(50),
+            4,
+        )?;
+        assert!(!stats.is_empty());
+        assert!(stats.iter().all(|sample| sample.memory > 0));
+        Ok(())
+    }
+
+    // The children of the test are the test executable itself running only the ignored test
+    // below, which finds what to do in the environment.
+    #[cfg(feature = "mmap")]
+    const COUNTER_PATH: &str = "SHARED_COUNTER_PATH";
+    #[cfg(feature = "mmap")]
+    const COUNTER_TIMES: &str = "SHARED_COUNTER_TIMES";
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn shared_counter() -> io::Result<()> {
+        const TIMES: u64 = 10_000;
+        let path = std::env::temp_dir().join(format!("shared_counter_test_{}", std::process::id()));
+        let value = count_in_three_processes(&path, TIMES, || {
+            let mut child = Command::new(std::env::current_exe()?);
+            child
+                .args(["--exact", "tests::increment_shared_counter", "--ignored"])
+                .env(COUNTER_PATH, &path)
+                .env(COUNTER_TIMES, TIMES.to_string())
+                .stdout(Stdio::null());
+            Ok(child)
+        })?;
+        assert_eq!(value, 3 * TIMES);
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    #[ignore = "child process of shared_counter"]
+    fn increment_shared_counter() -> io::Result<()> {
+        let (Ok(path), Ok(times)) = (std::env::var(COUNTER_PATH), std::env::var(COUNTER_TIMES))
+        else {
+            return Ok(());
+        };
+        increment_child([path, times].into_iter())
+    }
+
+    // Needs a real named pipe, run it with --ignored.
+    #[cfg(all(windows, feature = "interprocess"))]
+    #[test]
+    #[ignore]
+    fn named_pipe_echo() -> io::Result<()> {
+        let name = format!("rust_learning_echo_test_{}", std::process::id());
+        {
+            let name = name.clone();
+            std::thread::spawn(move || serve_named_pipe(&name));
+        }
+
+        let mut response = Err(io::Error::from(io::ErrorKind::NotFound));
+        for _ in 0..50 {
+            response = query_named_pipe(&name, b"ping");
+            if response.is_ok() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert_eq!(response?, b"ping");
+        let payload: Vec<u8> = (0..=255).collect();
+        assert_eq!(query_named_pipe(&name, &payload)?, payload);
+        Ok(())
+    }
+
+    #[cfg(all(windows, feature = "interprocess"))]
+    #[test]
+    fn message_framing() -> io::Result<()> {
+        let mut framed = Vec::new();
+        write_message(&mut framed, b"ping")?;
+        assert_eq!(framed, b"\x04\0\0\0ping");
+        assert_eq!(read_message(&mut framed.as_slice())?, b"ping");
+
+        // A length over the limit is rejected before allocating anything.
+        let huge = (MAX_MESSAGE_LEN as u32 + 1).to_le_bytes();
+        let err = read_message(&mut huge.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn graceful_termination() -> io::Result<()> {
+        use std::os::unix::process::ExitStatusExt;
+
+        // The script catches SIGTERM and exits cleanly with code 0.
+        let status = run_forwarding_sigterm(
+            Command::new("sh")
+                .arg("-c")
+                .arg("trap 'echo cleaning up; exit 0' TERM; while true; do sleep 0.05; done")
+                .stdout(Stdio::null()),
+            Duration::from_millis(200),
+        )?;
+        assert!(status.success());
+
+        // A process that ignores SIGTERM is killed after the grace period.
+        let status = run_forwarding_sigterm(
+            Command::new("sh")
+                .arg("-c")
+                .arg("trap '' TERM; while true; do sleep 0.05; done"),
+            Duration::from_millis(100),
+        )?;
+        assert_eq!(status.signal(), Some(libc::SIGKILL));
+
+        // A process ending by itself is not signaled at all.
+        let status = run_forwarding_sigterm(&mut Command::new("true"), Duration::from_secs(5))?;
+        assert!(status.success());
+        Ok(())
+    }
+
+    #[test]
+    fn rpc() -> io::Result<()> {
+        let requests = concat!(
+            r#"{"id": 1, "method": "sum", "params": [1, 2, 3]}"#,
+            "\n",
+            r#"{"id": 2, "method": "echo", "params": {"text": "hi"}}"#,
+            "\n\n",
+            r#"{"id": 3, "method": "divide", "params": [1, 0]}"#,
+            "\n",
+            r#"{"id": 4, "method": "sum", "params": ["a"]}"#,
+            "\n",
+            r#"{"id": 5, "method": "sum", "params": [9223372036854775807, 1]}"#,
+            "\n",
+            "not json\n",
+        );
+        let mut output = Cursor::new(Vec::new());
+        serve_rpc(Cursor::new(requests), &mut output, &rpc_handlers())?;
+
+        let responses: Vec<Value> = String::from_utf8(output.into_inner())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 6);
+        assert_eq!(responses[0], json!({"id": 1, "result": 6}));
+        assert_eq!(responses[1], json!({"id": 2, "result": {"text": "hi"}}));
+        assert_eq!(responses[2]["id"], 3);
+        assert_eq!(responses[2]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[3]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(responses[4]["id"], 5);
+        assert_eq!(responses[4]["error"]["code"], INVALID_PARAMS);
+        assert_eq!(responses[5]["id"], Value::Null);
+        assert_eq!(responses[5]["error"]["code"], PARSE_ERROR);
+        Ok(())
+    }
+}
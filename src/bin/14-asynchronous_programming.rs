@@ -206,3 +206,1429 @@
 // "
 //
 //
+// ------------------------------------ EXAMPLES ----------------------------------------------
+//
+// The following examples don't use any external runtime, they build the minimum needed to run
+// futures on top of the std Future trait to show what tokio does for us.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BinaryHeap, VecDeque};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread, ThreadId};
+use std::time::{Duration, Instant};
+
+// COUNTDOWN FUTURE
+//
+// The simplest hand written state machine: it returns Pending `remaining` times and then Ready.
+// Every time it returns Pending it wakes itself up, otherwise nobody would poll it again.
+struct CountdownFuture {
+    remaining: u32,
+}
+
+impl CountdownFuture {
+    fn new(remaining: u32) -> Self {
+        CountdownFuture { remaining }
+    }
+}
+
+impl Future for CountdownFuture {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.remaining == 0 {
+            Poll::Ready(())
+        } else {
+            self.remaining -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+// EXECUTOR
+//
+// A Waker is built from anything implementing the Wake trait, here waking means unparking the
+// thread that is running the executor.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn thread_waker() -> Waker {
+    Arc::new(ThreadWaker(thread::current())).into()
+}
+
+// Runs a single future to completion on the current thread, sleeping while it is Pending.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let waker = thread_waker();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+// LOCAL EXECUTOR
+//
+// A cooperative scheduler for many tasks on a single thread: the futures are boxed (they all
+// have different types) and pinned (they can't move once polled), each one gets polled in turn
+// and goes back at the end of the queue while it is Pending. A task that returned Ready is
+// dropped and never polled again. Since everything runs on one thread the tasks don't need to be
+// Send and can share state through Rc.
+struct LocalExecutor {
+    tasks: VecDeque<Pin<Box<dyn Future<Output = ()>>>>,
+}
+
+impl LocalExecutor {
+    fn new() -> Self {
+        LocalExecutor {
+            tasks: VecDeque::new(),
+        }
+    }
+
+    fn spawn(&mut self, task: impl Future<Output = ()> + 'static) {
+        self.tasks.push_back(Box::pin(task));
+    }
+
+    fn run(&mut self) {
+        let waker = thread_waker();
+        let mut cx = Context::from_waker(&waker);
+        while let Some(mut task) = self.tasks.pop_front() {
+            if task.as_mut().poll(&mut cx).is_pending() {
+                self.tasks.push_back(task);
+            }
+        }
+    }
+}
+
+// Wraps a future counting how many times it gets polled.
+struct CountPolls<F> {
+    inner: F,
+    polls: Rc<Cell<u32>>,
+}
+
+impl<F: Future + Unpin> Future for CountPolls<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        self.polls.set(self.polls.get() + 1);
+        Pin::new(&mut self.inner).poll(cx)
+    }
+}
+
+// Spawns a countdown task for each length and runs them all, returns the lengths in the order
+// the tasks finished and how many times each countdown was polled.
+fn run_countdowns(lengths: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut executor = LocalExecutor::new();
+    let finished = Rc::new(RefCell::new(Vec::new()));
+    let polls: Vec<Rc<Cell<u32>>> = lengths.iter().map(|_| Rc::new(Cell::new(0))).collect();
+    for (&length, polls) in lengths.iter().zip(&polls) {
+        let finished = Rc::clone(&finished);
+        let countdown = CountPolls {
+            inner: CountdownFuture::new(length),
+            polls: Rc::clone(polls),
+        };
+        executor.spawn(async move {
+            countdown.await;
+            finished.borrow_mut().push(length);
+        });
+    }
+    executor.run();
+    let finished = finished.borrow().clone();
+    (finished, polls.iter().map(|p| p.get()).collect())
+}
+
+fn local_executor_example() {
+    let (finished, polls) = run_countdowns(&[5, 1, 3, 0]);
+    println!("local executor: tasks finished in order {finished:?}, polled {polls:?} times");
+}
+
+// ASYNC MUTEX
+//
+// A std::sync::Mutex blocks the whole thread while waiting, on a single threaded executor that
+// would block every other task too (including the one holding the lock). An async mutex instead
+// returns a future from lock(): if the value is free the future is Ready with a guard, otherwise
+// it stores the waker of the task and returns Pending, so the executor can run other tasks.
+// The value is moved out of the mutex into the guard and moved back when the guard is dropped,
+// that's also the moment in which the first queued task gets woken up.
+// The std Mutex inside is only held for the few instructions that touch the state, never across
+// an await.
+struct AsyncMutex<T> {
+    state: Mutex<AsyncMutexState<T>>,
+}
+
+struct AsyncMutexState<T> {
+    value: Option<T>,
+    waiters: VecDeque<Waker>,
+}
+
+impl<T> AsyncMutex<T> {
+    fn new(value: T) -> Self {
+        AsyncMutex {
+            state: Mutex::new(AsyncMutexState {
+                value: Some(value),
+                waiters: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> AsyncLock<'_, T> {
+        AsyncLock { mutex: self }
+    }
+}
+
+struct AsyncLock<'a, T> {
+    mutex: &'a AsyncMutex<T>,
+}
+
+impl<'a, T> Future for AsyncLock<'a, T> {
+    type Output = AsyncMutexGuard<'a, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.mutex.state.lock().unwrap();
+        match state.value.take() {
+            Some(value) => Poll::Ready(AsyncMutexGuard {
+                mutex: self.mutex,
+                value: Some(value),
+            }),
+            None => {
+                if !state.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                    state.waiters.push_back(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+struct AsyncMutexGuard<'a, T> {
+    mutex: &'a AsyncMutex<T>,
+    value: Option<T>,
+}
+
+impl<T> Deref for AsyncMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for AsyncMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for AsyncMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        let mut state = self.mutex.state.lock().unwrap();
+        state.value = self.value.take();
+        if let Some(waiter) = state.waiters.pop_front() {
+            waiter.wake();
+        }
+    }
+}
+
+// Every task reads the value, yields to the executor while holding the guard and then writes
+// the incremented value. Without the lock the other tasks would read the same old value during
+// the yield and some increments would get lost. Returns the final value of the counter.
+fn increment_with_yields(tasks: u32, increments: u32) -> u32 {
+    let counter = Rc::new(AsyncMutex::new(0));
+    let mut executor = LocalExecutor::new();
+    for _ in 0..tasks {
+        let counter = Rc::clone(&counter);
+        executor.spawn(async move {
+            for _ in 0..increments {
+                let mut guard = counter.lock().await;
+                let read = *guard;
+                CountdownFuture::new(1).await;
+                *guard = read + 1;
+            }
+        });
+    }
+    executor.run();
+    let total = *block_on(counter.lock());
+    total
+}
+
+fn async_mutex_example() {
+    println!(
+        "async mutex: {} of 50 increments, none lost",
+        increment_with_yields(5, 10)
+    );
+}
+
+// ASYNC SEMAPHORE
+//
+// Same idea as the async mutex but with a number of permits instead of a single value: acquire()
+// resolves to a Permit when one is available and the Permit gives it back on drop.
+// It's the usual way to limit how many tasks run a certain section at the same time (e.g. how
+// many connections are opened to a server) without blocking the executor thread.
+struct AsyncSemaphore {
+    state: Mutex<AsyncSemaphoreState>,
+}
+
+struct AsyncSemaphoreState {
+    permits: usize,
+    waiters: VecDeque<Waker>,
+}
+
+impl AsyncSemaphore {
+    fn new(permits: usize) -> Self {
+        AsyncSemaphore {
+            state: Mutex::new(AsyncSemaphoreState {
+                permits,
+                waiters: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn acquire(&self) -> Acquire<'_> {
+        Acquire { semaphore: self }
+    }
+}
+
+struct Acquire<'a> {
+    semaphore: &'a AsyncSemaphore,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = Permit<'a>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Permit<'a>> {
+        let mut state = self.semaphore.state.lock().unwrap();
+        if state.permits > 0 {
+            state.permits -= 1;
+            Poll::Ready(Permit {
+                semaphore: self.semaphore,
+            })
+        } else {
+            if !state.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                state.waiters.push_back(cx.waker().clone());
+            }
+            Poll::Pending
+        }
+    }
+}
+
+struct Permit<'a> {
+    semaphore: &'a AsyncSemaphore,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.semaphore.state.lock().unwrap();
+        state.permits += 1;
+        if let Some(waiter) = state.waiters.pop_front() {
+            waiter.wake();
+        }
+    }
+}
+
+// Six countdown tasks of growing length share the permits, returns the highest number of tasks
+// running at the same time and how many are still running at the end.
+fn max_running_tasks(permits: usize) -> (usize, usize) {
+    let semaphore = Rc::new(AsyncSemaphore::new(permits));
+    let running = Rc::new(Cell::new(0));
+    let max_running = Rc::new(Cell::new(0));
+    let mut executor = LocalExecutor::new();
+    for length in 1..=6 {
+        let semaphore = Rc::clone(&semaphore);
+        let running = Rc::clone(&running);
+        let max_running = Rc::clone(&max_running);
+        executor.spawn(async move {
+            let _permit = semaphore.acquire().await;
+            running.set(running.get() + 1);
+            max_running.set(max_running.get().max(running.get()));
+            CountdownFuture::new(length).await;
+            running.set(running.get() - 1);
+        });
+    }
+    executor.run();
+    (max_running.get(), running.get())
+}
+
+fn async_semaphore_example() {
+    let (max_running, _) = max_running_tasks(2);
+    println!("async semaphore: at most {max_running} of 6 tasks running with 2 permits");
+}
+
+// ASYNC RETRY
+//
+// An async fn can use loops and early returns like any other function, the compiler turns them
+// into states of the generated state machine. A future can be awaited only once, so to retry we
+// need a closure that builds a fresh future for every attempt.
+// At least one attempt is always made, otherwise there would be no error to return.
+async fn retry_async<T, E, Fut, F>(mut make_future: F, attempts: usize) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match make_future().await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt >= attempts => return Err(error),
+            Err(_) => attempt += 1,
+        }
+    }
+}
+
+// Retries an operation that fails the first two times, returns the result and how many times
+// the operation was called.
+fn retry_flaky(attempts: usize) -> (Result<u32, String>, u32) {
+    let calls = Cell::new(0);
+    let flaky = || {
+        calls.set(calls.get() + 1);
+        let call = calls.get();
+        async move {
+            CountdownFuture::new(1).await;
+            if call < 3 {
+                Err(format!("attempt {} failed", call))
+            } else {
+                Ok(call)
+            }
+        }
+    };
+    let result = block_on(retry_async(flaky, attempts));
+    (result, calls.get())
+}
+
+fn retry_async_example() {
+    let (result, calls) = retry_flaky(5);
+    println!("retry async: {result:?} after {calls} calls");
+}
+
+// ASYNC QUEUE
+//
+// A bounded queue shared by tasks. push() returns a future that completes when there is room
+// for the item, pop() one that completes when there is an item: a fast producer is slowed
+// down to the speed of the consumer (backpressure) instead of filling the memory.
+// Both sides keep a list of wakers, a pop wakes the first waiting pusher because it made room
+// and a push wakes the first waiting popper because it brought an item.
+// The Push future holds the item until it can be stored, so it must be movable: it never
+// gets pinned fields, so it can be Unpin even when T isn't.
+struct AsyncQueue<T> {
+    state: Mutex<AsyncQueueState<T>>,
+}
+
+struct AsyncQueueState<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    pushers: VecDeque<Waker>,
+    poppers: VecDeque<Waker>,
+}
+
+fn register(waiters: &mut VecDeque<Waker>, waker: &Waker) {
+    if !waiters.iter().any(|w| w.will_wake(waker)) {
+        waiters.push_back(waker.clone());
+    }
+}
+
+impl<T> AsyncQueue<T> {
+    fn new(capacity: usize) -> Self {
+        AsyncQueue {
+            state: Mutex::new(AsyncQueueState {
+                items: VecDeque::new(),
+                capacity: capacity.max(1),
+                pushers: VecDeque::new(),
+                poppers: VecDeque::new(),
+            }),
+        }
+    }
+
+    fn push(&self, item: T) -> Push<'_, T> {
+        Push {
+            queue: self,
+            item: Some(item),
+        }
+    }
+
+    fn pop(&self) -> Pop<'_, T> {
+        Pop { queue: self }
+    }
+}
+
+struct Push<'a, T> {
+    queue: &'a AsyncQueue<T>,
+    item: Option<T>,
+}
+
+impl<T> Unpin for Push<'_, T> {}
+
+impl<T> Future for Push<'_, T> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let queue = self.queue;
+        let mut state = queue.state.lock().unwrap();
+        if state.items.len() < state.capacity {
+            let item = self.item.take().expect("Push polled after completion");
+            state.items.push_back(item);
+            if let Some(popper) = state.poppers.pop_front() {
+                popper.wake();
+            }
+            Poll::Ready(())
+        } else {
+            register(&mut state.pushers, cx.waker());
+            Poll::Pending
+        }
+    }
+}
+
+struct Pop<'a, T> {
+    queue: &'a AsyncQueue<T>,
+}
+
+impl<T> Future for Pop<'_, T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.queue.state.lock().unwrap();
+        match state.items.pop_front() {
+            Some(item) => {
+                if let Some(pusher) = state.pushers.pop_front() {
+                    pusher.wake();
+                }
+                Poll::Ready(item)
+            }
+            None => {
+                register(&mut state.poppers, cx.waker());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+// The producer pushes ten items in a queue of three as fast as it can, counting the pushes
+// that weren't completed at the first poll; the consumer takes some polls for every item.
+// Returns the items received, the pushes that waited and the items left in the queue.
+fn produce_and_consume() -> (Vec<i32>, u32, usize) {
+    let queue = Rc::new(AsyncQueue::new(3));
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let waited_pushes = Rc::new(Cell::new(0));
+    let mut executor = LocalExecutor::new();
+
+    let (producer_queue, waited) = (Rc::clone(&queue), Rc::clone(&waited_pushes));
+    executor.spawn(async move {
+        for i in 0..10 {
+            let polls = Rc::new(Cell::new(0));
+            CountPolls {
+                inner: producer_queue.push(i),
+                polls: Rc::clone(&polls),
+            }
+            .await;
+            if polls.get() > 1 {
+                waited.set(waited.get() + 1);
+            }
+        }
+    });
+    let (consumer_queue, consumer_received) = (Rc::clone(&queue), Rc::clone(&received));
+    executor.spawn(async move {
+        for _ in 0..10 {
+            let item = consumer_queue.pop().await;
+            consumer_received.borrow_mut().push(item);
+            CountdownFuture::new(3).await;
+        }
+    });
+    executor.run();
+    let left = queue.state.lock().unwrap().items.len();
+    let received = received.borrow().clone();
+    (received, waited_pushes.get(), left)
+}
+
+fn async_queue_example() {
+    let (received, waited_pushes, _) = produce_and_consume();
+    println!("async queue: received {received:?}, {waited_pushes} pushes waited for room");
+}
+
+// REACTOR
+//
+// The part of a runtime that waits for events on behalf of the futures (tokio's driver). Here
+// the only events are timers: the reactor keeps them in a BinaryHeap ordered by deadline, with
+// the earliest one on top (Ord is reversed because BinaryHeap is a max-heap), and its run()
+// loop sleeps on a Condvar until that deadline, then wakes the expired timers. Registering a
+// timer notifies the Condvar because the new deadline might be earlier than the one the loop
+// is sleeping for. The wakers are called after releasing the lock, a waker could register a
+// new timer right away.
+// There is one reactor per process, started with its thread the first time it is used.
+struct Timer {
+    deadline: Instant,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl PartialEq for Timer {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Timer {}
+
+impl PartialOrd for Timer {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timer {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.deadline.cmp(&self.deadline)
+    }
+}
+
+struct Reactor {
+    timers: Mutex<BinaryHeap<Timer>>,
+    changed: Condvar,
+    thread: Thread,
+}
+
+static REACTOR: OnceLock<Reactor> = OnceLock::new();
+
+impl Reactor {
+    fn global() -> &'static Reactor {
+        REACTOR.get_or_init(|| {
+            let handle = thread::Builder::new()
+                .name("reactor".to_string())
+                .spawn(|| Reactor::global().run())
+                .expect("failed to start the reactor thread");
+            Reactor {
+                timers: Mutex::new(BinaryHeap::new()),
+                changed: Condvar::new(),
+                thread: handle.thread().clone(),
+            }
+        })
+    }
+
+    fn register(&self, deadline: Instant, waker: Arc<Mutex<Option<Waker>>>) {
+        self.timers.lock().unwrap().push(Timer { deadline, waker });
+        self.changed.notify_one();
+    }
+
+    fn run(&self) {
+        let mut timers = self.timers.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let mut expired = Vec::new();
+            while timers.peek().is_some_and(|t| t.deadline <= now) {
+                expired.push(timers.pop().unwrap());
+            }
+            if !expired.is_empty() {
+                drop(timers);
+                for timer in expired {
+                    if let Some(waker) = timer.waker.lock().unwrap().take() {
+                        waker.wake();
+                    }
+                }
+                timers = self.timers.lock().unwrap();
+                continue;
+            }
+            timers = match timers.peek() {
+                Some(next) => {
+                    let wait = next.deadline - now;
+                    self.changed.wait_timeout(timers, wait).unwrap().0
+                }
+                None => self.changed.wait(timers).unwrap(),
+            };
+        }
+    }
+}
+
+// DELAY
+//
+// A timer future like tokio::time::sleep. It is Ready once the deadline has passed, before
+// that it must make sure someone will wake the task at the deadline, otherwise an executor
+// that sleeps while the tasks are Pending would never poll it again. At the first poll the
+// timer is registered with the reactor, which wakes the last waker it has been given at the
+// deadline (a task can be polled by different wakers over time, so the waker is stored in a
+// shared slot and replaced at every poll). A thousand delays still use the single reactor
+// thread, instead of a sleeping thread each.
+struct Delay {
+    deadline: Instant,
+    waker: Option<Arc<Mutex<Option<Waker>>>>,
+}
+
+impl Delay {
+    fn new(duration: Duration) -> Self {
+        Delay::until(Instant::now() + duration)
+    }
+
+    fn until(deadline: Instant) -> Self {
+        Delay {
+            deadline,
+            waker: None,
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        match &self.waker {
+            Some(slot) => *slot.lock().unwrap() = Some(cx.waker().clone()),
+            None => {
+                let slot = Arc::new(Mutex::new(Some(cx.waker().clone())));
+                Reactor::global().register(self.deadline, Arc::clone(&slot));
+                self.waker = Some(slot);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+// STREAMS AND TICKER
+//
+// A stream is the async version of an iterator: poll_next() returns Pending while the next item
+// isn't ready, Ready(Some(item)) when it is and Ready(None) when the stream has ended. This is
+// a reduced copy of the Stream trait of the futures crate, with next() returning a future so
+// that a stream can be consumed with `while let Some(x) = stream.next().await`.
+// Ticker is a stream of tick counts like tokio::time::interval: each tick is a Delay, and the
+// next deadline is computed from the previous deadline (not from when the tick was seen) so
+// the ticks don't drift when the consumer is late.
+trait SimpleStream {
+    type Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+
+    fn next(&mut self) -> Next<'_, Self>
+    where
+        Self: Unpin + Sized,
+    {
+        Next { stream: self }
+    }
+}
+
+struct Next<'a, S> {
+    stream: &'a mut S,
+}
+
+impl<S: SimpleStream + Unpin> Future for Next<'_, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+        Pin::new(&mut *self.stream).poll_next(cx)
+    }
+}
+
+struct Ticker {
+    interval: Duration,
+    ticks: u64,
+    delay: Delay,
+}
+
+impl Ticker {
+    fn new(interval: Duration) -> Self {
+        Ticker {
+            interval,
+            ticks: 0,
+            delay: Delay::new(interval),
+        }
+    }
+}
+
+impl SimpleStream for Ticker {
+    type Item = u64;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<u64>> {
+        if Pin::new(&mut self.delay).poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+        let next_deadline = self.delay.deadline + self.interval;
+        self.delay = Delay::until(next_deadline);
+        self.ticks += 1;
+        Poll::Ready(Some(self.ticks))
+    }
+}
+
+fn ticker_example() {
+    let interval = Duration::from_millis(20);
+    let start = Instant::now();
+    block_on(async {
+        let mut ticker = Ticker::new(interval);
+        while let Some(tick) = ticker.next().await {
+            println!("ticker: tick {tick} after {:?}", start.elapsed());
+            if tick == 3 {
+                break;
+            }
+        }
+    });
+}
+
+// JOIN ALL AND AGGREGATE
+//
+// join_all() turns a Vec of futures into one future that completes when all of them have,
+// returning their outputs in the same order (like the join! macro but for any number of
+// futures of the same type). Each poll polls the futures that are still running, the finished
+// ones keep their output until the last one is done. The futures are boxed so they stay pinned
+// while the Vec is moved around.
+// aggregate() awaits them all and passes the outputs to a combining function (fan-out, fan-in).
+enum JoinSlot<F: Future> {
+    Running(Pin<Box<F>>),
+    Done(Option<F::Output>),
+}
+
+struct JoinAll<F: Future> {
+    slots: Vec<JoinSlot<F>>,
+}
+
+fn join_all<F: Future>(futures: Vec<F>) -> JoinAll<F> {
+    JoinAll {
+        slots: futures
+            .into_iter()
+            .map(|f| JoinSlot::Running(Box::pin(f)))
+            .collect(),
+    }
+}
+
+impl<F: Future> Unpin for JoinAll<F> {}
+
+impl<F: Future> Future for JoinAll<F> {
+    type Output = Vec<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Vec<F::Output>> {
+        let mut all_done = true;
+        for slot in self.slots.iter_mut() {
+            if let JoinSlot::Running(future) = slot {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(output) => *slot = JoinSlot::Done(Some(output)),
+                    Poll::Pending => all_done = false,
+                }
+            }
+        }
+        if !all_done {
+            return Poll::Pending;
+        }
+        let outputs = self
+            .slots
+            .iter_mut()
+            .map(|slot| match slot {
+                JoinSlot::Done(output) => output.take().expect("JoinAll polled after completion"),
+                JoinSlot::Running(_) => unreachable!(),
+            })
+            .collect();
+        Poll::Ready(outputs)
+    }
+}
+
+async fn aggregate<T, Fut: Future<Output = T>>(
+    futures: Vec<Fut>,
+    combine: impl Fn(Vec<T>) -> T,
+) -> T {
+    combine(join_all(futures).await)
+}
+
+fn aggregate_example() {
+    let values = [(5, 10u64), (1, 20), (3, 30), (2, 40)];
+    let futures: Vec<_> = values
+        .iter()
+        .map(|&(length, value)| async move {
+            CountdownFuture::new(length).await;
+            value
+        })
+        .collect();
+    let total = block_on(aggregate(futures, |outputs| outputs.into_iter().sum()));
+    println!("aggregate: sum of 4 futures is {total}");
+}
+
+// CANCELLATION TOKEN
+//
+// In Rust a future is cancelled by dropping it, but the code that wants to cancel doesn't own
+// it. A CancellationToken is the shared flag they agree on (cooperative cancellation): clones
+// of it go to the tasks, cancel() sets the flag and wakes every task waiting on cancelled().
+// The waiter checks the flag again after registering its waker, otherwise a cancel() happening
+// between the first check and the registration would never wake it.
+// cancellable() races a future against the token: the token is polled first, so if both are
+// ready the cancellation wins, and the losing future is dropped with the race.
+#[derive(Clone)]
+struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+struct CancellationState {
+    cancelled: AtomicBool,
+    waiters: Mutex<VecDeque<Waker>>,
+}
+
+impl CancellationToken {
+    fn new() -> Self {
+        CancellationToken {
+            inner: Arc::new(CancellationState {
+                cancelled: AtomicBool::new(false),
+                waiters: Mutex::new(VecDeque::new()),
+            }),
+        }
+    }
+
+    fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        let waiters = std::mem::take(&mut *self.inner.waiters.lock().unwrap());
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled { token: self }
+    }
+}
+
+struct Cancelled<'a> {
+    token: &'a CancellationToken,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        register(&mut self.token.inner.waiters.lock().unwrap(), cx.waker());
+        if self.token.is_cancelled() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+// Resolves to Some(output) if the future completes first, None if the token is cancelled first.
+struct Cancellable<'a, F> {
+    future: Pin<Box<F>>,
+    cancelled: Cancelled<'a>,
+}
+
+fn cancellable<F: Future>(future: F, token: &CancellationToken) -> Cancellable<'_, F> {
+    Cancellable {
+        future: Box::pin(future),
+        cancelled: token.cancelled(),
+    }
+}
+
+impl<F: Future> Future for Cancellable<'_, F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<F::Output>> {
+        if Pin::new(&mut self.cancelled).poll(cx).is_ready() {
+            return Poll::Ready(None);
+        }
+        self.future.as_mut().poll(cx).map(Some)
+    }
+}
+
+fn cancellation_example() {
+    let token = CancellationToken::new();
+    let remote = token.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        remote.cancel();
+    });
+    let polls = Rc::new(Cell::new(0));
+    let long_job = CountPolls {
+        inner: CountdownFuture::new(u32::MAX),
+        polls: Rc::clone(&polls),
+    };
+    let outcome = block_on(cancellable(long_job, &token));
+    handle.join().unwrap();
+    println!(
+        "cancellation: long countdown ended with {outcome:?} after {} polls",
+        polls.get()
+    );
+}
+
+// CONCURRENCY LIMIT
+//
+// join_all() polls every future at once, with thousands of requests that means thousands of
+// open connections. run_limited() keeps at most `limit` of them running (like
+// buffer_unordered from the futures crate): the others wait in a queue and one is started
+// every time a running one completes. A future that hasn't been polled yet hasn't started
+// anything, so keeping it in the queue is enough to hold it back.
+// Futures complete in any order, so each one remembers its index and its output is stored in
+// that slot, the results come back in the order of the input.
+// poll_fn() builds a future from a closure, which saves writing a struct for a one-off poll.
+async fn run_limited<T, Fut: Future<Output = T>>(tasks: Vec<Fut>, limit: usize) -> Vec<T> {
+    let limit = limit.max(1);
+    let mut results: Vec<Option<T>> = tasks.iter().map(|_| None).collect();
+    let mut waiting: VecDeque<(usize, Fut)> = tasks.into_iter().enumerate().collect();
+    let mut running: Vec<(usize, Pin<Box<Fut>>)> = Vec::new();
+
+    std::future::poll_fn(|cx| loop {
+        while running.len() < limit {
+            match waiting.pop_front() {
+                Some((index, task)) => running.push((index, Box::pin(task))),
+                None => break,
+            }
+        }
+        let before = running.len();
+        running.retain_mut(|(index, task)| match task.as_mut().poll(cx) {
+            Poll::Ready(output) => {
+                results[*index] = Some(output);
+                false
+            }
+            Poll::Pending => true,
+        });
+        if running.is_empty() && waiting.is_empty() {
+            return Poll::Ready(());
+        }
+        // Nothing completed: every running task is Pending and has registered its waker.
+        // Otherwise the freed slots are filled and the new tasks polled right away.
+        if running.len() == before {
+            return Poll::Pending;
+        }
+    })
+    .await;
+
+    results
+        .into_iter()
+        .map(|output| output.expect("every task has completed"))
+        .collect()
+}
+
+// Runs a countdown task for each length with at most `limit` of them at once. Each task returns
+// ten times its index; returns the results and the highest number of tasks active together.
+fn run_countdowns_limited(lengths: &[u32], limit: usize) -> (Vec<usize>, usize) {
+    let active = Rc::new(Cell::new(0));
+    let peak = Rc::new(Cell::new(0));
+    let tasks: Vec<_> = lengths
+        .iter()
+        .enumerate()
+        .map(|(i, &length)| {
+            let (active, peak) = (Rc::clone(&active), Rc::clone(&peak));
+            async move {
+                active.set(active.get() + 1);
+                peak.set(peak.get().max(active.get()));
+                CountdownFuture::new(length).await;
+                active.set(active.get() - 1);
+                i * 10
+            }
+        })
+        .collect();
+    let results = block_on(run_limited(tasks, limit));
+    (results, peak.get())
+}
+
+fn run_limited_example() {
+    let (results, peak) = run_countdowns_limited(&[4, 1, 6, 2, 2, 5, 0, 3], 3);
+    println!("run limited: 8 tasks, at most {peak} at once: {results:?}");
+}
+
+// Forwards the wakes to the real waker, writing down which thread each one came from.
+struct RecordingWaker {
+    inner: Waker,
+    woken_from: Arc<Mutex<Vec<ThreadId>>>,
+}
+
+impl Wake for RecordingWaker {
+    fn wake(self: Arc<Self>) {
+        self.woken_from.lock().unwrap().push(thread::current().id());
+        self.inner.wake_by_ref();
+    }
+}
+
+struct Recorded<F> {
+    inner: Pin<Box<F>>,
+    woken_from: Arc<Mutex<Vec<ThreadId>>>,
+}
+
+impl<F: Future> Future for Recorded<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let waker = Waker::from(Arc::new(RecordingWaker {
+            inner: cx.waker().clone(),
+            woken_from: Arc::clone(&self.woken_from),
+        }));
+        self.inner.as_mut().poll(&mut Context::from_waker(&waker))
+    }
+}
+
+fn reactor_example() {
+    let woken_from = Arc::new(Mutex::new(Vec::new()));
+    let start = Instant::now();
+    let delays: Vec<_> = [50, 10, 30, 20, 40]
+        .into_iter()
+        .map(|ms| Recorded {
+            inner: Box::pin(Delay::new(Duration::from_millis(ms))),
+            woken_from: Arc::clone(&woken_from),
+        })
+        .collect();
+    let finished = block_on(join_all(delays));
+    let mut threads = woken_from.lock().unwrap().clone();
+    threads.dedup();
+    let reactor = &Reactor::global().thread;
+    println!(
+        "reactor: {} delays done after {:?}, woken from {} thread ({:?}: {})",
+        finished.len(),
+        start.elapsed(),
+        threads.len(),
+        reactor.name().unwrap_or("unnamed"),
+        threads.iter().all(|&id| id == reactor.id())
+    );
+}
+
+fn main() {
+    local_executor_example();
+    async_mutex_example();
+    async_semaphore_example();
+    retry_async_example();
+    async_queue_example();
+    ticker_example();
+    aggregate_example();
+    cancellation_example();
+    run_limited_example();
+    reactor_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    // One per hand-polled future: it counts how many times that future has been woken.
+    #[derive(Default)]
+    struct CountingWaker {
+        wakes: AtomicUsize,
+    }
+
+    impl CountingWaker {
+        fn wakes(&self) -> usize {
+            self.wakes.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wakes.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn counting_waker() -> (Arc<CountingWaker>, Waker) {
+        let counter = Arc::new(CountingWaker::default());
+        (Arc::clone(&counter), Waker::from(counter))
+    }
+
+    #[test]
+    fn local_executor() {
+        block_on(CountdownFuture::new(3));
+
+        let (finished, polls) = run_countdowns(&[5, 1, 3, 0]);
+        // Shorter countdowns end first since all the tasks advance one step per round.
+        assert_eq!(finished, vec![0, 1, 3, 5]);
+        // A countdown of n returns Pending n times and Ready once, then it isn't polled anymore.
+        assert_eq!(polls, vec![6, 2, 4, 1]);
+    }
+
+    #[test]
+    fn async_mutex() {
+        assert_eq!(increment_with_yields(5, 10), 50);
+        assert_eq!(increment_with_yields(1, 3), 3);
+    }
+
+    #[test]
+    fn async_mutex_guard_drop_wakes_first_waiter() {
+        let mutex = AsyncMutex::new(0);
+        let guard = block_on(mutex.lock());
+
+        let (first, first_waker) = counting_waker();
+        let (second, second_waker) = counting_waker();
+        let mut first_lock = std::pin::pin!(mutex.lock());
+        let mut second_lock = std::pin::pin!(mutex.lock());
+        let mut first_cx = Context::from_waker(&first_waker);
+        let mut second_cx = Context::from_waker(&second_waker);
+        assert!(first_lock.as_mut().poll(&mut first_cx).is_pending());
+        assert!(second_lock.as_mut().poll(&mut second_cx).is_pending());
+
+        // Dropping the guard wakes only the lock that started waiting first.
+        drop(guard);
+        assert_eq!((first.wakes(), second.wakes()), (1, 0));
+        let Poll::Ready(mut guard) = first_lock.as_mut().poll(&mut first_cx) else {
+            panic!("the woken lock should get the value");
+        };
+        *guard += 1;
+        assert!(second_lock.as_mut().poll(&mut second_cx).is_pending());
+
+        drop(guard);
+        assert_eq!((first.wakes(), second.wakes()), (1, 1));
+        let Poll::Ready(guard) = second_lock.as_mut().poll(&mut second_cx) else {
+            panic!("the woken lock should get the value");
+        };
+        assert_eq!(*guard, 1);
+    }
+
+    #[test]
+    fn async_semaphore() {
+        assert_eq!(max_running_tasks(2), (2, 0));
+        assert_eq!(max_running_tasks(1), (1, 0));
+    }
+
+    #[test]
+    fn permit_drop_wakes_first_waiter() {
+        let semaphore = AsyncSemaphore::new(1);
+        let permit = block_on(semaphore.acquire());
+
+        let (first, first_waker) = counting_waker();
+        let (second, second_waker) = counting_waker();
+        let mut first_acquire = std::pin::pin!(semaphore.acquire());
+        let mut second_acquire = std::pin::pin!(semaphore.acquire());
+        let mut first_cx = Context::from_waker(&first_waker);
+        let mut second_cx = Context::from_waker(&second_waker);
+        assert!(first_acquire.as_mut().poll(&mut first_cx).is_pending());
+        assert!(second_acquire.as_mut().poll(&mut second_cx).is_pending());
+
+        // Giving the permit back wakes only the acquire that started waiting first.
+        drop(permit);
+        assert_eq!((first.wakes(), second.wakes()), (1, 0));
+        let Poll::Ready(permit) = first_acquire.as_mut().poll(&mut first_cx) else {
+            panic!("the woken acquire should get the permit");
+        };
+        assert!(second_acquire.as_mut().poll(&mut second_cx).is_pending());
+
+        drop(permit);
+        assert_eq!((first.wakes(), second.wakes()), (1, 1));
+        assert!(second_acquire.as_mut().poll(&mut second_cx).is_ready());
+    }
+
+    #[test]
+    fn retry_async_works() {
+        assert_eq!(retry_flaky(5), (Ok(3), 3));
+        // With fewer attempts than needed the last error is returned.
+        assert_eq!(retry_flaky(2), (Err("attempt 2 failed".to_string()), 2));
+    }
+
+    #[test]
+    fn async_queue() {
+        let (received, waited_pushes, left) = produce_and_consume();
+        assert_eq!(received, (0..10).collect::<Vec<_>>());
+        assert!(waited_pushes > 0);
+        assert_eq!(left, 0);
+    }
+
+    #[test]
+    fn push_wakes_first_waiting_pop() {
+        let queue = AsyncQueue::new(1);
+        let (first, first_waker) = counting_waker();
+        let (second, second_waker) = counting_waker();
+        let mut first_pop = std::pin::pin!(queue.pop());
+        let mut second_pop = std::pin::pin!(queue.pop());
+        let mut first_cx = Context::from_waker(&first_waker);
+        let mut second_cx = Context::from_waker(&second_waker);
+        assert!(first_pop.as_mut().poll(&mut first_cx).is_pending());
+        assert!(second_pop.as_mut().poll(&mut second_cx).is_pending());
+
+        // Every stored item wakes one pop, the one that started waiting first.
+        block_on(queue.push(1));
+        assert_eq!((first.wakes(), second.wakes()), (1, 0));
+        assert_eq!(first_pop.as_mut().poll(&mut first_cx), Poll::Ready(1));
+        block_on(queue.push(2));
+        assert_eq!((first.wakes(), second.wakes()), (1, 1));
+        assert_eq!(second_pop.as_mut().poll(&mut second_cx), Poll::Ready(2));
+    }
+
+    #[test]
+    fn pop_wakes_first_waiting_push() {
+        let queue = AsyncQueue::new(1);
+        block_on(queue.push(0));
+        let (first, first_waker) = counting_waker();
+        let (second, second_waker) = counting_waker();
+        let mut first_push = queue.push(1);
+        let mut second_push = queue.push(2);
+        let mut first_cx = Context::from_waker(&first_waker);
+        let mut second_cx = Context::from_waker(&second_waker);
+        assert!(Pin::new(&mut first_push).poll(&mut first_cx).is_pending());
+        assert!(Pin::new(&mut second_push).poll(&mut second_cx).is_pending());
+
+        // Every taken item frees a place and wakes one push, the one that started waiting first.
+        assert_eq!(block_on(queue.pop()), 0);
+        assert_eq!((first.wakes(), second.wakes()), (1, 0));
+        assert!(Pin::new(&mut first_push).poll(&mut first_cx).is_ready());
+        assert!(Pin::new(&mut second_push).poll(&mut second_cx).is_pending());
+        assert_eq!(block_on(queue.pop()), 1);
+        assert_eq!((first.wakes(), second.wakes()), (1, 1));
+        assert!(Pin::new(&mut second_push).poll(&mut second_cx).is_ready());
+        assert_eq!(block_on(queue.pop()), 2);
+    }
+
+    #[test]
+    fn ticker() {
+        let interval = Duration::from_millis(20);
+        let ticks = Rc::new(RefCell::new(Vec::new()));
+        let task_ticks = Rc::clone(&ticks);
+        let start = Instant::now();
+        let mut executor = LocalExecutor::new();
+        executor.spawn(async move {
+            let mut ticker = Ticker::new(interval);
+            while let Some(tick) = ticker.next().await {
+                task_ticks.borrow_mut().push((tick, start.elapsed()));
+                if tick == 3 {
+                    break;
+                }
+            }
+        });
+        executor.run();
+
+        let ticks = ticks.borrow();
+        assert_eq!(ticks.iter().map(|t| t.0).collect::<Vec<_>>(), vec![1, 2, 3]);
+        for (tick, elapsed) in ticks.iter() {
+            assert!(*elapsed >= interval * *tick as u32);
+        }
+        assert!(start.elapsed() >= 3 * interval);
+        // block_on sleeps between the polls, the reactor wakes it up.
+        let start = Instant::now();
+        block_on(Delay::new(interval));
+        assert!(start.elapsed() >= interval);
+    }
+
+    #[test]
+    fn aggregate_works() {
+        let polls = Rc::new(Cell::new(0));
+        let values = [(5, 10u64), (1, 20), (3, 30), (2, 40)];
+        let futures: Vec<_> = values
+            .iter()
+            .map(|&(length, value)| {
+                let polls = Rc::clone(&polls);
+                async move {
+                    CountPolls {
+                        inner: CountdownFuture::new(length),
+                        polls,
+                    }
+                    .await;
+                    value
+                }
+            })
+            .collect();
+
+        let total = Rc::new(Cell::new(0));
+        let task_total = Rc::clone(&total);
+        let mut executor = LocalExecutor::new();
+        executor.spawn(async move {
+            task_total.set(aggregate(futures, |outputs| outputs.into_iter().sum()).await);
+        });
+        executor.run();
+        assert_eq!(total.get(), 100);
+        // Every countdown has been polled once per step plus the final one.
+        assert_eq!(polls.get(), values.iter().map(|v| v.0 + 1).sum::<u32>());
+
+        let order = block_on(join_all(vec![
+            Box::pin(async { "first" }) as Pin<Box<dyn Future<Output = &str>>>,
+            Box::pin(async {
+                CountdownFuture::new(3).await;
+                "second"
+            }),
+        ]));
+        assert_eq!(order, vec!["first", "second"]);
+        let empty: Vec<u64> = block_on(join_all(Vec::<std::future::Ready<u64>>::new()));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn cancellation() {
+        let token = CancellationToken::new();
+        let outcome = Rc::new(Cell::new(None));
+        let polls = Rc::new(Cell::new(0));
+        let mut executor = LocalExecutor::new();
+
+        let (task_token, task_outcome, task_polls) =
+            (token.clone(), Rc::clone(&outcome), Rc::clone(&polls));
+        executor.spawn(async move {
+            let long_job = CountPolls {
+                inner: CountdownFuture::new(u32::MAX),
+                polls: task_polls,
+            };
+            task_outcome.set(Some(cancellable(long_job, &task_token).await));
+        });
+        let canceller = token.clone();
+        executor.spawn(async move {
+            CountdownFuture::new(5).await;
+            canceller.cancel();
+        });
+        executor.run();
+
+        // The race ended on the cancellation branch long before the countdown could finish.
+        assert_eq!(outcome.get(), Some(None));
+        assert!(token.is_cancelled());
+        assert_eq!(polls.get(), 6);
+
+        // A cancel() from another thread wakes the task through the registered waker.
+        let token = CancellationToken::new();
+        let remote = token.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            remote.cancel();
+        });
+        let waited = block_on(async {
+            token.cancelled().await;
+            "woken"
+        });
+        handle.join().unwrap();
+        assert_eq!(waited, "woken");
+
+        // A future that finishes first wins the race, once cancelled every new race returns None.
+        let token = CancellationToken::new();
+        assert_eq!(block_on(cancellable(async { 42 }, &token)), Some(42));
+        token.cancel();
+        assert_eq!(block_on(cancellable(async { 42 }, &token)), None);
+    }
+
+    #[test]
+    fn run_limited_works() {
+        let (results, peak) = run_countdowns_limited(&[4, 1, 6, 2, 2, 5, 0, 3], 3);
+        // Results are in input order even though the short tasks finished first.
+        assert_eq!(results, vec![0, 10, 20, 30, 40, 50, 60, 70]);
+        assert_eq!(peak, 3);
+
+        // A limit larger than the tasks behaves like join_all, a limit of 0 is treated as 1.
+        let quick: Vec<_> = (1..=2).map(std::future::ready).collect();
+        assert_eq!(block_on(run_limited(quick, 10)), vec![1, 2]);
+        assert_eq!(run_countdowns_limited(&[2; 4], 0).1, 1);
+    }
+
+    #[test]
+    fn reactor() {
+        let woken_from = Arc::new(Mutex::new(Vec::new()));
+        let start = Instant::now();
+        let delays: Vec<_> = [50, 10, 30, 20, 40]
+            .into_iter()
+            .map(|ms| Recorded {
+                inner: Box::pin(async move {
+                    Delay::new(Duration::from_millis(ms)).await;
+                    (ms, start.elapsed())
+                }),
+                woken_from: Arc::clone(&woken_from),
+            })
+            .collect();
+        let finished = block_on(join_all(delays));
+
+        for (ms, elapsed) in &finished {
+            assert!(*elapsed >= Duration::from_millis(*ms));
+        }
+        // Every delay was woken, and always from the same thread: the reactor's one.
+        let woken_from = woken_from.lock().unwrap();
+        assert_eq!(woken_from.len(), finished.len());
+        assert!(woken_from
+            .iter()
+            .all(|&id| id == Reactor::global().thread.id()));
+        assert_ne!(Reactor::global().thread.id(), thread::current().id());
+        assert_eq!(Reactor::global().thread.name(), Some("reactor"));
+    }
+}
@@ -224,3 +224,1152 @@
 // - Names of struct, enum, traits and other predefined rust keywords should be in UpperCamelCase.
 // - Names of variables, functions, methods and other non-predefined rust keywords should be in
 //   lower_snake_case.
+//
+// ---------------- EXAMPLES ------------------
+//
+// Small exercises using the features described above.
+
+use std::collections::HashMap;
+
+// SUM OF DIGITS
+//
+// Integer division and remainder by 10 peel off one decimal digit per iteration.
+// The loop is an expression too: 'while' returns () so the sum is returned after it.
+fn digit_sum(n: u64) -> u32 {
+    let mut n = n;
+    let mut sum = 0;
+    while n > 0 {
+        sum += (n % 10) as u32;
+        n /= 10;
+    }
+    sum
+}
+
+fn number_of_digits(n: u64) -> u32 {
+    if n == 0 {
+        1
+    } else {
+        n.ilog10() + 1
+    }
+}
+
+fn digit_sum_example() {
+    println!(
+        "digit sum: 1234 -> {}, u64::MAX ({} digits) -> {}",
+        digit_sum(1234),
+        number_of_digits(u64::MAX),
+        digit_sum(u64::MAX)
+    );
+}
+
+// ROMAN NUMERALS
+//
+// The table pairs each value with its symbol from the biggest to the smallest, subtractive forms
+// (CM, CD, XC, ...) included, so converting to roman is a greedy loop over it.
+// Parsing uses match to translate every symbol and adds or subtracts it depending on the symbol
+// that follows. Many invalid strings (like "IIII" or "IC") would still produce a number, so the
+// result is accepted only if converting it back gives the same string.
+const ROMAN_TABLE: [(u32, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+fn to_roman(n: u32) -> Option<String> {
+    if !(1..=3999).contains(&n) {
+        return None;
+    }
+    let mut n = n;
+    let mut roman = String::new();
+    for &(value, symbol) in ROMAN_TABLE.iter() {
+        while n >= value {
+            roman.push_str(symbol);
+            n -= value;
+        }
+    }
+    Some(roman)
+}
+
+fn from_roman(s: &str) -> Option<u32> {
+    let values = s
+        .chars()
+        .map(|c| match c {
+            'I' => Some(1),
+            'V' => Some(5),
+            'X' => Some(10),
+            'L' => Some(50),
+            'C' => Some(100),
+            'D' => Some(500),
+            'M' => Some(1000),
+            _ => None,
+        })
+        .collect::<Option<Vec<u32>>>()?;
+
+    let mut total = 0;
+    for (i, &value) in values.iter().enumerate() {
+        match values.get(i + 1) {
+            Some(&next) if next > value => total -= value as i64,
+            _ => total += value as i64,
+        }
+    }
+    let total = u32::try_from(total).ok()?;
+    match to_roman(total) {
+        Some(canonical) if canonical == s => Some(total),
+        _ => None,
+    }
+}
+
+fn roman_example() {
+    let roman = to_roman(1994).unwrap();
+    println!(
+        "roman numerals: 1994 -> {roman} -> {:?}",
+        from_roman(&roman)
+    );
+}
+
+// SLICE UTILITIES
+//
+// Generic functions working on slices of any type: the bounds after T say which traits the type
+// has to implement, Clone because the results are new Vecs owning copies of the elements and
+// PartialEq because consecutive elements have to be compared.
+fn flatten<T: Clone>(nested: &[Vec<T>]) -> Vec<T> {
+    nested
+        .iter()
+        .flat_map(|inner| inner.iter().cloned())
+        .collect()
+}
+
+fn dedup_consecutive<T: PartialEq + Clone>(data: &[T]) -> Vec<T> {
+    let mut result: Vec<T> = Vec::new();
+    for item in data {
+        if result.last() != Some(item) {
+            result.push(item.clone());
+        }
+    }
+    result
+}
+
+fn slice_utilities_example() {
+    println!(
+        "slice utilities: {:?}",
+        dedup_consecutive(&flatten(&[vec![1, 1], vec![1, 2]]))
+    );
+}
+
+// BASE64
+//
+// Every 3 bytes (24 bits) of input become 4 characters of 6 bits each taken from the alphabet.
+// When the input length isn't a multiple of 3 the last group is completed with zero bits and
+// '=' characters are appended so that the output length is always a multiple of 4.
+// Those completing zero bits end up in the last character before the padding, which carries
+// 2 (with "==") or 4 (with "=") bits more than the data needs. The decoder requires them to
+// be zero: otherwise "Zh==" would decode to the same byte as "Zg==" and the same data would
+// have more than one encoding.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+#[derive(Debug, PartialEq)]
+enum DecodeError {
+    Length(usize),
+    Character { position: usize, character: char },
+    Padding,
+    TrailingBits,
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let group = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn base64_value(byte: u8) -> Option<u32> {
+    match byte {
+        b'A'..=b'Z' => Some((byte - b'A') as u32),
+        b'a'..=b'z' => Some((byte - b'a' + 26) as u32),
+        b'0'..=b'9' => Some((byte - b'0' + 52) as u32),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+fn base64_decode(s: &str) -> Result<Vec<u8>, DecodeError> {
+    // Characters are checked first so the error can report the whole char even when it is a
+    // multibyte one, after this check every byte is ASCII.
+    if let Some((position, character)) = s
+        .char_indices()
+        .find(|&(_, c)| c != '=' && !(c.is_ascii() && base64_value(c as u8).is_some()))
+    {
+        return Err(DecodeError::Character {
+            position,
+            character,
+        });
+    }
+    let bytes = s.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return Err(DecodeError::Length(bytes.len()));
+    }
+
+    let mut decoded = Vec::with_capacity(bytes.len() / 4 * 3);
+    let groups = bytes.len() / 4;
+    for (g, chunk) in bytes.chunks(4).enumerate() {
+        // Padding is allowed only at the end of the last group: "xx==" or "xxx=".
+        let padding = chunk.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 || (padding > 0 && g + 1 != groups) {
+            return Err(DecodeError::Padding);
+        }
+        let mut group = 0;
+        for (i, &byte) in chunk[..4 - padding].iter().enumerate() {
+            // The only non alphabet byte left is a '=' in the middle of a group.
+            group |= base64_value(byte).ok_or(DecodeError::Padding)? << (18 - 6 * i);
+        }
+        let group = group.to_be_bytes();
+        // The bytes cut by the padding must be made only of the completing zero bits.
+        if group[4 - padding..].iter().any(|&b| b != 0) {
+            return Err(DecodeError::TrailingBits);
+        }
+        decoded.extend_from_slice(&group[1..4 - padding]);
+    }
+    Ok(decoded)
+}
+
+fn base64_example() {
+    let encoded = base64_encode(b"foobar");
+    println!(
+        "base64: foobar -> {encoded} -> {:?}, \"Zm9\" -> {:?}",
+        base64_decode(&encoded).map(String::from_utf8),
+        base64_decode("Zm9")
+    );
+}
+
+// RUN LENGTH ENCODING
+//
+// Consecutive equal bytes are stored as a (byte, count) tuple. The count is a u8 so a run longer
+// than 255 bytes is split in more tuples with the same byte.
+fn rle_encode(data: &[u8]) -> Vec<(u8, u8)> {
+    let mut pairs: Vec<(u8, u8)> = Vec::new();
+    for &byte in data {
+        match pairs.last_mut() {
+            Some((last, count)) if *last == byte && *count < u8::MAX => *count += 1,
+            _ => pairs.push((byte, 1)),
+        }
+    }
+    pairs
+}
+
+fn rle_decode(pairs: &[(u8, u8)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for &(byte, count) in pairs {
+        data.extend(std::iter::repeat_n(byte, count as usize));
+    }
+    data
+}
+
+fn rle_example() {
+    let pairs = rle_encode(b"aaabcc");
+    println!(
+        "run length encoding: aaabcc -> {pairs:?} -> {:?}",
+        String::from_utf8(rle_decode(&pairs)).unwrap()
+    );
+}
+
+// EXPRESSION EVALUATOR
+//
+// An arithmetic expression is a tree: each variant of the enum is a node, the operators hold
+// their operands in a Box since an enum can't contain itself directly (its size would be
+// infinite). eval is a match with one arm for each variant, and since match is an expression
+// every arm produces the value of its node. The ? operator returns the error of an operand
+// before its parent is computed.
+#[derive(Debug)]
+enum Expr {
+    Num(f64),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, PartialEq)]
+enum EvalError {
+    DivisionByZero,
+}
+
+fn eval(e: &Expr) -> Result<f64, EvalError> {
+    Ok(match e {
+        Expr::Num(n) => *n,
+        Expr::Add(a, b) => eval(a)? + eval(b)?,
+        Expr::Sub(a, b) => eval(a)? - eval(b)?,
+        Expr::Mul(a, b) => eval(a)? * eval(b)?,
+        Expr::Div(a, b) => {
+            let divisor = eval(b)?;
+            if divisor == 0.0 {
+                return Err(EvalError::DivisionByZero);
+            }
+            eval(a)? / divisor
+        }
+    })
+}
+
+fn num(n: f64) -> Box<Expr> {
+    Box::new(Expr::Num(n))
+}
+
+fn eval_example() {
+    // (1 + 2) * 3
+    let e = Expr::Mul(Box::new(Expr::Add(num(1.0), num(2.0))), num(3.0));
+    println!("eval: (1 + 2) * 3 = {:?}", eval(&e));
+    // 1 + 5 / (2 - 2)
+    let e = Expr::Add(
+        num(1.0),
+        Box::new(Expr::Div(num(5.0), Box::new(Expr::Sub(num(2.0), num(2.0))))),
+    );
+    println!("eval: 1 + 5 / (2 - 2) = {:?}", eval(&e));
+}
+
+// TOKENIZER
+//
+// Before an expression like "12 + 3*4" can become an Expr its characters have to be grouped
+// in tokens. char_indices gives every character together with its byte offset, peekable lets
+// the number arm look at the next character without consuming it so that consecutive digits
+// become a single number. An unknown character stops the loop with its offset, and so does a
+// number that doesn't fit in a u64: checked_mul and checked_add return None on overflow
+// instead of wrapping around (in release builds) or panicking (in debug builds).
+#[derive(Debug, PartialEq)]
+enum Token {
+    Num(u64),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, PartialEq)]
+enum LexError {
+    UnexpectedChar { position: usize, character: char },
+    // The position is the one of the first digit.
+    NumberTooLarge { position: usize },
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            ' ' | '\t' | '\n' => continue,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '*' => Token::Star,
+            '/' => Token::Slash,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '0'..='9' => {
+                let mut value = c.to_digit(10).unwrap() as u64;
+                while let Some(&(_, d @ '0'..='9')) = chars.peek() {
+                    value = value
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(d.to_digit(10).unwrap() as u64))
+                        .ok_or(LexError::NumberTooLarge { position })?;
+                    chars.next();
+                }
+                Token::Num(value)
+            }
+            character => {
+                return Err(LexError::UnexpectedChar {
+                    position,
+                    character,
+                })
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn tokenize_example() {
+    println!("tokenize: \"12 + 3*4\" -> {:?}", tokenize("12 + 3*4"));
+    println!("tokenize: \"1 + x\" -> {:?}", tokenize("1 + x"));
+    println!(
+        "tokenize: \"99999999999999999999\" -> {:?}",
+        tokenize("99999999999999999999")
+    );
+}
+
+// SLICE PATTERNS
+//
+// The match on a slice from the section above, with the same arms in the same order. The arms
+// are tried from the first one, so a slice that matches more of them gets the description of
+// the earliest: [0, 4] starts with 0 even if it also ends with a value in 3..=5, and [5] ends
+// with 5 even if it has a single element. v @ binds the matched value to a name.
+fn describe_slice(values: &[i32]) -> String {
+    match values {
+        [0, ..] => String::from("starts with 0"),
+        [.., v @ 3..=5] => format!("ends with {v}"),
+        [_, v, ..] => format!("second value is {v}"),
+        [v] => format!("single element {v}"),
+        [] => String::from("empty"),
+    }
+}
+
+fn describe_slice_example() {
+    for values in [&[0, 4][..], &[1, 2, 3], &[9], &[]] {
+        println!("describe slice: {values:?} {}", describe_slice(values));
+    }
+}
+
+// STRING AND &STR
+//
+// A function that only reads text takes &str: a &String is converted automatically (deref
+// coercion) and so is a string literal. A function that has to create new text returns a
+// String, the caller owns it. borrow_len takes &String on purpose to show that len() is the
+// same method reached through the deref, usually it would take &str.
+// len() counts the bytes of the UTF-8 encoding, chars() the characters: they differ as soon
+// as a character needs more than one byte ('é' takes 2, '€' 3).
+fn to_owned_upper(s: &str) -> String {
+    s.to_uppercase()
+}
+
+#[allow(clippy::ptr_arg)]
+fn borrow_len(s: &String) -> usize {
+    s.len()
+}
+
+fn concat_all(parts: &[&str]) -> String {
+    let mut joined = String::with_capacity(parts.iter().map(|p| p.len()).sum());
+    for part in parts {
+        joined.push_str(part);
+    }
+    joined
+}
+
+fn strings_example() {
+    let owned = String::from("café");
+    println!(
+        "strings: {owned:?} is {} bytes and {} chars, upper {:?}, borrowed len {}",
+        owned.len(),
+        owned.chars().count(),
+        to_owned_upper(&owned),
+        borrow_len(&owned)
+    );
+    println!(
+        "strings: {:?}",
+        concat_all(&["caffè", " ", "e", " ", "cornetto", " €2"])
+    );
+}
+
+// FIZZBUZZ
+//
+// The two remainders are matched together as a tuple, so each arm describes a combination.
+// The order matters: (0, 0) has to come before the arms with a single 0. The other arms use
+// '|' and '..=' to list explicitly the remainders left (1 or 2 for 3, 1 to 4 for 5).
+// The compiler doesn't know that a remainder is smaller than the divisor, so the match must
+// also cover the impossible values to be exhaustive.
+fn fizzbuzz(n: u32) -> String {
+    match (n % 3, n % 5) {
+        (0, 0) => String::from("FizzBuzz"),
+        (0, 1..=4) => String::from("Fizz"),
+        (1 | 2, 0) => String::from("Buzz"),
+        (1 | 2, 1..=4) => n.to_string(),
+        (3.., _) | (_, 5..) => unreachable!("remainders are smaller than the divisor"),
+    }
+}
+
+fn fizzbuzz_example() {
+    let output: Vec<String> = (1..=20).map(fizzbuzz).collect();
+    println!("fizzbuzz: {}", output.join(" "));
+}
+
+// BOOLEAN EXPRESSIONS
+//
+// Another tree of enum variants, this time with names: a Var is looked up in the environment
+// and an undefined one is an error. And and Or evaluate the right operand only when the left
+// one doesn't decide the result already (short-circuit, like && and ||), so an undefined
+// variable on the right of `false AND ...` is never looked up.
+enum BoolExpr {
+    Lit(bool),
+    Var(String),
+    Not(Box<BoolExpr>),
+    And(Box<BoolExpr>, Box<BoolExpr>),
+    Or(Box<BoolExpr>, Box<BoolExpr>),
+}
+
+fn eval_bool(e: &BoolExpr, env: &HashMap<String, bool>) -> Result<bool, String> {
+    match e {
+        BoolExpr::Lit(value) => Ok(*value),
+        BoolExpr::Var(name) => env
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("undefined variable '{name}'")),
+        BoolExpr::Not(inner) => Ok(!eval_bool(inner, env)?),
+        BoolExpr::And(a, b) => Ok(eval_bool(a, env)? && eval_bool(b, env)?),
+        BoolExpr::Or(a, b) => Ok(eval_bool(a, env)? || eval_bool(b, env)?),
+    }
+}
+
+fn var(name: &str) -> Box<BoolExpr> {
+    Box::new(BoolExpr::Var(name.to_string()))
+}
+
+fn bool_expr_example() {
+    let env: HashMap<String, bool> = [("raining", true), ("umbrella", false)]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    // raining AND NOT umbrella
+    let wet = BoolExpr::And(var("raining"), Box::new(BoolExpr::Not(var("umbrella"))));
+    println!(
+        "bool expr: raining AND NOT umbrella = {:?}",
+        eval_bool(&wet, &env)
+    );
+    let unknown = BoolExpr::Or(var("umbrella"), var("snowing"));
+    let skipped = BoolExpr::Or(Box::new(BoolExpr::Lit(true)), var("snowing"));
+    println!(
+        "bool expr: umbrella OR snowing = {:?}",
+        eval_bool(&unknown, &env)
+    );
+    println!(
+        "bool expr: true OR snowing = {:?}, snowing is never looked up",
+        eval_bool(&skipped, &env)
+    );
+}
+
+// ARRAY STATISTICS
+//
+// A fixed-size array coerces to a slice, so the same function works for [i32; 3] and [i32; 5].
+// The sum is accumulated in an i64: adding many large i32 values would overflow an i32
+// accumulator (a panic in debug builds, a silent wrap in release ones).
+fn array_stats(a: &[i32]) -> Option<(i32, i32, f64)> {
+    let (&first, rest) = a.split_first()?;
+    let mut min = first;
+    let mut max = first;
+    let mut sum = first as i64;
+    for &x in rest {
+        min = min.min(x);
+        max = max.max(x);
+        sum += x as i64;
+    }
+    Some((min, max, sum as f64 / a.len() as f64))
+}
+
+fn array_stats_example() {
+    let values = [4, 8, 15, 16, 23, 42];
+    println!("array stats of {values:?}: {:?}", array_stats(&values));
+    println!("array stats of []: {:?}", array_stats(&[]));
+}
+
+// PARSING A RANGE
+//
+// The ranges described above written as text: "1..5" and "1..=5". split_once cuts the string
+// at the first "..", an '=' at the start of what is left makes the range inclusive. Each bound
+// is trimmed before parsing so " 1 ..= 5 " is accepted too. A missing bound is an error:
+// "..5" would be a valid RangeTo in code but here there is no value to return for it.
+#[derive(Debug, PartialEq)]
+enum RangeParseError {
+    MissingSeparator,
+    MissingStart,
+    MissingEnd,
+    InvalidBound(String),
+}
+
+fn parse_bound(text: &str, missing: RangeParseError) -> Result<i64, RangeParseError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(missing);
+    }
+    text.parse()
+        .map_err(|_| RangeParseError::InvalidBound(text.to_string()))
+}
+
+fn parse_range(s: &str) -> Result<(i64, i64, bool), RangeParseError> {
+    let (start, rest) = s
+        .split_once("..")
+        .ok_or(RangeParseError::MissingSeparator)?;
+    let (end, inclusive) = match rest.strip_prefix('=') {
+        Some(end) => (end, true),
+        None => (rest, false),
+    };
+    let start = parse_bound(start, RangeParseError::MissingStart)?;
+    let end = parse_bound(end, RangeParseError::MissingEnd)?;
+    Ok((start, end, inclusive))
+}
+
+fn parse_range_example() {
+    for text in ["1..5", " -3 ..= 7 ", "..5"] {
+        println!("parse range: {text:?} -> {:?}", parse_range(text));
+    }
+}
+
+// VEC GROWTH
+//
+// The regrowth described in the VEC section, observed from outside: capacity() is the size of
+// the buffer, len() how much of it is used. The capacity is recorded every time a push changes
+// it, i.e. at every reallocation. The exact numbers are an implementation detail of std (today
+// a Vec<u8> starts at 8 and doubles), what is guaranteed is that the growth is geometric, so
+// pushing n elements costs O(log n) reallocations and O(1) amortized per push.
+fn simulate_growth(n: usize) -> Vec<usize> {
+    let mut v: Vec<u8> = Vec::new();
+    let mut capacities = Vec::new();
+    for i in 0..n {
+        v.push(i as u8);
+        if capacities.last() != Some(&v.capacity()) {
+            capacities.push(v.capacity());
+        }
+    }
+    capacities
+}
+
+fn simulate_growth_example() {
+    let capacities = simulate_growth(1000);
+    println!("vec growth: capacities for 1000 pushes {capacities:?}");
+}
+
+// SWAPPING THROUGH A &mut
+//
+// A value can't be moved out of a &mut (`let tmp = *a;` doesn't compile for a String, it would
+// leave *a without a value). mem::take moves the value out and leaves T::default() in its
+// place, mem::replace moves a new value in and returns the old one: both keep the reference
+// pointing to a valid value at every step. The Default bound is the price of the take, the
+// placeholder is an empty String or Vec which doesn't allocate.
+// Only the three-field headers of the String or Vec move, the heap buffers stay where they are
+// (std::mem::swap does the same without needing Default).
+fn swap_vals<T: Default>(a: &mut T, b: &mut T) {
+    let old_a = std::mem::take(a);
+    *a = std::mem::replace(b, old_a);
+}
+
+fn swap_vals_example() {
+    let mut first = String::from("first");
+    let mut second = String::from("second");
+    swap_vals(&mut first, &mut second);
+    let mut short = vec![1, 2];
+    let mut long = vec![3, 4, 5, 6];
+    swap_vals(&mut short, &mut long);
+    println!("swap vals: {first} {second}, {short:?} {long:?}");
+}
+
+// INSPECTING A CHAR
+//
+// A char is always 4 bytes in memory and holds a Unicode scalar value, `as u32` gives its code
+// point. Inside a String the same char is stored in UTF-8, which uses 1 to 4 bytes depending
+// on the value: this is why s.len() (bytes) and s.chars().count() can differ.
+// is_alphabetic and is_numeric follow the Unicode tables, not only the ASCII ranges.
+fn inspect_char(c: char) -> (u32, bool, bool, usize) {
+    (c as u32, c.is_alphabetic(), c.is_numeric(), c.len_utf8())
+}
+
+fn inspect_char_example() {
+    for c in ['A', '9', 'é', '😀'] {
+        println!("inspect char: {c:?} -> {:?}", inspect_char(c));
+    }
+}
+
+// ROUND TRIP FUZZING
+//
+// An encoder and its decoder can be checked together on any input: decoding what was encoded
+// must give back the original. The tests use proptest to generate the inputs: byte vectors of
+// every length from 0 to 299 (so every remainder modulo 3 of base64 is covered) with bytes
+// over the whole 0..=255 range, and when a check fails proptest shrinks the input to a minimal
+// failing one. The targeted cases are the boundaries where the bugs usually are: the three
+// padding lengths with all-zero and all-one bits, and runs just below, at and above the 255
+// limit of a RLE count. Decoding random strings made of alphabet characters and '=' must
+// return Ok or Err but never panic, whatever the position of the padding.
+fn round_trip_example() {
+    let data = [0xff, 0x00, 0x00, 0x00, 0x7f];
+    let encoded = base64_encode(&data);
+    let pairs = rle_encode(&data);
+    println!(
+        "round trip: {data:?} -> {encoded} -> {:?}, rle {pairs:?} -> {:?}",
+        base64_decode(&encoded),
+        rle_decode(&pairs)
+    );
+}
+
+fn main() {
+    digit_sum_example();
+    roman_example();
+    slice_utilities_example();
+    base64_example();
+    rle_example();
+    eval_example();
+    tokenize_example();
+    describe_slice_example();
+    strings_example();
+    fizzbuzz_example();
+    bool_expr_example();
+    array_stats_example();
+    parse_range_example();
+    simulate_growth_example();
+    swap_vals_example();
+    inspect_char_example();
+    round_trip_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    #[test]
+    fn digit_sum_works() {
+        assert_eq!(digit_sum(0), 0);
+        assert_eq!(digit_sum(7), 7);
+        assert_eq!(digit_sum(1234), 10);
+        assert_eq!(digit_sum(u64::MAX), 87);
+        assert_eq!(number_of_digits(0), 1);
+        assert_eq!(number_of_digits(1000), 4);
+    }
+
+    proptest! {
+        // Every digit is at most 9.
+        #[test]
+        fn digit_sum_is_at_most_nine_per_digit(n: u64) {
+            prop_assert!(digit_sum(n) <= 9 * number_of_digits(n));
+        }
+
+        // Appending a trailing zero doesn't change the sum.
+        #[test]
+        fn digit_sum_ignores_trailing_zeros(n in 0..=u64::MAX / 10) {
+            prop_assert_eq!(digit_sum(n * 10), digit_sum(n));
+        }
+    }
+
+    #[test]
+    fn roman() {
+        assert_eq!(to_roman(1).as_deref(), Some("I"));
+        assert_eq!(to_roman(4).as_deref(), Some("IV"));
+        assert_eq!(to_roman(1994).as_deref(), Some("MCMXCIV"));
+        assert_eq!(to_roman(3999).as_deref(), Some("MMMCMXCIX"));
+        assert_eq!(to_roman(0), None);
+        assert_eq!(to_roman(4000), None);
+
+        assert_eq!(from_roman("XLII"), Some(42));
+        assert_eq!(from_roman("MMMCMXCIX"), Some(3999));
+        assert_eq!(from_roman(""), None);
+        assert_eq!(from_roman("IIII"), None);
+        assert_eq!(from_roman("IC"), None);
+        assert_eq!(from_roman("X1"), None);
+
+        for n in 1..=3999 {
+            assert_eq!(from_roman(&to_roman(n).unwrap()), Some(n));
+        }
+    }
+
+    #[test]
+    fn slice_utilities() {
+        assert_eq!(flatten::<i32>(&[]), Vec::<i32>::new());
+        assert_eq!(
+            flatten(&[vec![], vec![1], vec![], vec![2, 3]]),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            flatten(&[vec!["a".to_string()], vec!["b".to_string()]]),
+            vec!["a", "b"]
+        );
+
+        assert_eq!(dedup_consecutive::<i32>(&[]), Vec::<i32>::new());
+        assert_eq!(dedup_consecutive(&[7, 7, 7, 7]), vec![7]);
+        assert_eq!(dedup_consecutive(&[1, 2, 1, 2, 1]), vec![1, 2, 1, 2, 1]);
+        assert_eq!(dedup_consecutive(&[1, 1, 2, 3, 3, 1]), vec![1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn base64() {
+        // Test vectors from RFC 4648, section 10.
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded), Ok(plain.as_bytes().to_vec()));
+        }
+        assert_eq!(base64_encode(&[0xff, 0xfe, 0xfd]), "//79");
+        assert_eq!(base64_decode("//79"), Ok(vec![0xff, 0xfe, 0xfd]));
+
+        assert_eq!(base64_decode("Zm9"), Err(DecodeError::Length(3)));
+        assert_eq!(
+            base64_decode("Zm$v"),
+            Err(DecodeError::Character {
+                position: 2,
+                character: '$'
+            })
+        );
+        assert_eq!(
+            base64_decode("Zé=="),
+            Err(DecodeError::Character {
+                position: 1,
+                character: 'é'
+            })
+        );
+        assert_eq!(base64_decode("Z==="), Err(DecodeError::Padding));
+        assert_eq!(base64_decode("Z=g="), Err(DecodeError::Padding));
+        assert_eq!(base64_decode("Zg==Zm9v"), Err(DecodeError::Padding));
+        assert_eq!(base64_decode("Zg=="), Ok(b"f".to_vec()));
+        assert_eq!(base64_decode("Zh=="), Err(DecodeError::TrailingBits));
+        assert_eq!(base64_decode("Zm9="), Err(DecodeError::TrailingBits));
+    }
+
+    #[test]
+    fn rle() {
+        assert_eq!(rle_encode(&[]), vec![]);
+        assert_eq!(rle_decode(&[]), Vec::<u8>::new());
+        assert_eq!(rle_encode(&[9]), vec![(9, 1)]);
+        assert_eq!(rle_encode(b"aaabcc"), vec![(b'a', 3), (b'b', 1), (b'c', 2)]);
+        assert_eq!(rle_encode(&[0; 600]), vec![(0, 255), (0, 255), (0, 90)]);
+        assert_eq!(rle_decode(&[(1, 2), (3, 1)]), vec![1, 1, 3]);
+    }
+
+    proptest! {
+        // Round trip over inputs made of short and long runs.
+        #[test]
+        fn rle_round_trip_of_runs(runs in vec((0..4u8, 0..600usize), 0..20)) {
+            let data: Vec<u8> = runs
+                .into_iter()
+                .flat_map(|(byte, run)| std::iter::repeat_n(byte, run))
+                .collect();
+            prop_assert_eq!(rle_decode(&rle_encode(&data)), data);
+        }
+    }
+
+    #[test]
+    fn eval_works() {
+        // (1 + 2) * 3
+        let e = Expr::Mul(Box::new(Expr::Add(num(1.0), num(2.0))), num(3.0));
+        assert_eq!(eval(&e), Ok(9.0));
+        // 10 / 4 - 0.5
+        let e = Expr::Sub(Box::new(Expr::Div(num(10.0), num(4.0))), num(0.5));
+        assert_eq!(eval(&e), Ok(2.0));
+        // 1 + 5 / (2 - 2)
+        let e = Expr::Add(
+            num(1.0),
+            Box::new(Expr::Div(num(5.0), Box::new(Expr::Sub(num(2.0), num(2.0))))),
+        );
+        assert_eq!(eval(&e), Err(EvalError::DivisionByZero));
+    }
+
+    #[test]
+    fn tokenize_works() {
+        assert_eq!(
+            tokenize("12 + 3*4"),
+            Ok(vec![
+                Token::Num(12),
+                Token::Plus,
+                Token::Num(3),
+                Token::Star,
+                Token::Num(4)
+            ])
+        );
+        assert_eq!(
+            tokenize("(10-2)/ 4"),
+            Ok(vec![
+                Token::LParen,
+                Token::Num(10),
+                Token::Minus,
+                Token::Num(2),
+                Token::RParen,
+                Token::Slash,
+                Token::Num(4)
+            ])
+        );
+        assert_eq!(tokenize(""), Ok(vec![]));
+        assert_eq!(tokenize("   "), Ok(vec![]));
+        assert_eq!(
+            tokenize("1 + $2"),
+            Err(LexError::UnexpectedChar {
+                position: 4,
+                character: '$'
+            })
+        );
+        // Non ASCII characters are reported too, with the offset of their first byte.
+        assert_eq!(
+            tokenize("1é").unwrap_err(),
+            LexError::UnexpectedChar {
+                position: 1,
+                character: 'é'
+            }
+        );
+
+        // The largest u64 is still a number, one more overflows in either the multiplication
+        // or the addition.
+        assert_eq!(
+            tokenize("18446744073709551615"),
+            Ok(vec![Token::Num(u64::MAX)])
+        );
+        assert_eq!(
+            tokenize("1 + 18446744073709551616"),
+            Err(LexError::NumberTooLarge { position: 4 })
+        );
+        assert_eq!(
+            tokenize("(184467440737095516150)"),
+            Err(LexError::NumberTooLarge { position: 1 })
+        );
+    }
+
+    #[test]
+    fn describe_slice_works() {
+        assert_eq!(describe_slice(&[]), "empty");
+        assert_eq!(describe_slice(&[7]), "single element 7");
+        assert_eq!(describe_slice(&[0, 9, 9]), "starts with 0");
+        assert_eq!(describe_slice(&[1, 4]), "ends with 4");
+        assert_eq!(describe_slice(&[2, 2, 2, 4]), "ends with 4");
+        assert_eq!(describe_slice(&[2, 8, 6]), "second value is 8");
+        // Precedence: the earlier arm wins when more than one matches.
+        assert_eq!(describe_slice(&[0, 4]), "starts with 0");
+        assert_eq!(describe_slice(&[5]), "ends with 5");
+        assert_eq!(describe_slice(&[0]), "starts with 0");
+    }
+
+    #[test]
+    fn strings() {
+        let owned = String::from("café");
+        assert_eq!(to_owned_upper(&owned), "CAFÉ");
+        assert_eq!(to_owned_upper("rust"), "RUST");
+        assert_eq!(borrow_len(&owned), 5);
+        assert_eq!(owned.chars().count(), 4);
+        assert_eq!(borrow_len(&to_owned_upper(&owned)), 5);
+
+        let joined = concat_all(&["caffè", " ", "e", " ", "cornetto", " €2"]);
+        assert_eq!(joined, "caffè e cornetto €2");
+        assert_eq!(joined.chars().count(), 19);
+        assert_eq!(joined.len(), 22);
+        assert_eq!(concat_all(&[]), "");
+        // owned is still usable: the helpers only borrowed it.
+    }
+
+    #[test]
+    fn fizzbuzz_works() {
+        let expected = [
+            "1", "2", "Fizz", "4", "Buzz", "Fizz", "7", "8", "Fizz", "Buzz", "11", "Fizz", "13",
+            "14", "FizzBuzz", "16", "17", "Fizz", "19", "Buzz",
+        ];
+        let output: Vec<String> = (1..=20).map(fizzbuzz).collect();
+        assert_eq!(output, expected);
+        assert_eq!(fizzbuzz(0), "FizzBuzz");
+    }
+
+    #[test]
+    fn bool_expr() {
+        let env: HashMap<String, bool> = [("raining", true), ("umbrella", false)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+
+        // raining AND NOT umbrella
+        let wet = BoolExpr::And(var("raining"), Box::new(BoolExpr::Not(var("umbrella"))));
+        assert_eq!(eval_bool(&wet, &env), Ok(true));
+        // (umbrella OR NOT raining) OR false
+        let dry = BoolExpr::Or(
+            Box::new(BoolExpr::Or(
+                var("umbrella"),
+                Box::new(BoolExpr::Not(var("raining"))),
+            )),
+            Box::new(BoolExpr::Lit(false)),
+        );
+        assert_eq!(eval_bool(&dry, &env), Ok(false));
+
+        // The unknown variable is never evaluated when the left side decides.
+        let skipped = BoolExpr::And(var("umbrella"), var("snowing"));
+        assert_eq!(eval_bool(&skipped, &env), Ok(false));
+        let skipped = BoolExpr::Or(Box::new(BoolExpr::Lit(true)), var("snowing"));
+        assert_eq!(eval_bool(&skipped, &env), Ok(true));
+        let evaluated = BoolExpr::And(var("raining"), var("snowing"));
+        assert_eq!(
+            eval_bool(&evaluated, &env),
+            Err("undefined variable 'snowing'".to_string())
+        );
+    }
+
+    #[test]
+    fn array_stats_works() {
+        let values = [4, 8, 15, 16, 23, 42];
+        assert_eq!(array_stats(&values), Some((4, 42, 18.0)));
+        assert_eq!(array_stats(&[7]), Some((7, 7, 7.0)));
+        assert_eq!(array_stats(&[-3, -10, -2]), Some((-10, -2, -5.0)));
+        assert_eq!(array_stats(&[]), None);
+
+        // Summed in an i32 these would overflow; the i64 accumulator keeps the mean exact.
+        let big = [i32::MAX; 4];
+        assert_eq!(
+            array_stats(&big),
+            Some((i32::MAX, i32::MAX, i32::MAX as f64))
+        );
+    }
+
+    #[test]
+    fn parse_range_works() {
+        assert_eq!(parse_range("1..5"), Ok((1, 5, false)));
+        assert_eq!(parse_range("1..=5"), Ok((1, 5, true)));
+        assert_eq!(parse_range("  -3 ..= 7 "), Ok((-3, 7, true)));
+        // The parsed values behave like the range written in code.
+        let (start, end, inclusive) = parse_range("1..=5").unwrap();
+        let sum: i64 = if inclusive {
+            (start..=end).sum()
+        } else {
+            (start..end).sum()
+        };
+        assert_eq!(sum, (1..=5).sum());
+
+        assert_eq!(parse_range("..5"), Err(RangeParseError::MissingStart));
+        assert_eq!(parse_range("1..="), Err(RangeParseError::MissingEnd));
+        assert_eq!(parse_range("1-5"), Err(RangeParseError::MissingSeparator));
+        assert_eq!(
+            parse_range("1..x"),
+            Err(RangeParseError::InvalidBound("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn simulate_growth_works() {
+        let capacities = simulate_growth(1000);
+        assert!(capacities.windows(2).all(|w| w[0] <= w[1]));
+        assert!(*capacities.last().unwrap() >= 1000);
+        // 1000 pushes, only a handful of reallocations.
+        assert!(capacities.len() <= 12);
+        assert_eq!(simulate_growth(1), vec![capacities[0]]);
+        assert!(simulate_growth(0).is_empty());
+    }
+
+    #[test]
+    fn swap_vals_works() {
+        let mut first = String::from("first");
+        let mut second = String::from("second");
+        let (first_buffer, second_buffer) = (first.as_ptr(), second.as_ptr());
+        swap_vals(&mut first, &mut second);
+        assert_eq!((first.as_str(), second.as_str()), ("second", "first"));
+        // Same buffers in the other variable: the strings moved, nothing was cloned.
+        assert_eq!(
+            (first.as_ptr(), second.as_ptr()),
+            (second_buffer, first_buffer)
+        );
+
+        let mut short = vec![1, 2];
+        let mut long = vec![3, 4, 5, 6];
+        let long_buffer = long.as_ptr();
+        swap_vals(&mut short, &mut long);
+        assert_eq!(short, [3, 4, 5, 6]);
+        assert_eq!(long, [1, 2]);
+        assert_eq!(short.as_ptr(), long_buffer);
+
+        let mut x = 1;
+        let mut y = 2;
+        swap_vals(&mut x, &mut y);
+        assert_eq!((x, y), (2, 1));
+    }
+
+    #[test]
+    fn inspect_char_works() {
+        assert_eq!(inspect_char('A'), (65, true, false, 1));
+        assert_eq!(inspect_char('9'), (57, false, true, 1));
+        assert_eq!(inspect_char('é'), (0xE9, true, false, 2));
+        assert_eq!(inspect_char('€'), (0x20AC, false, false, 3));
+        assert_eq!(inspect_char('😀'), (0x1F600, false, false, 4));
+        // Roman numeral twelve: numeric even though it isn't an ASCII digit.
+        assert_eq!(inspect_char('Ⅻ'), (0x216B, true, true, 3));
+
+        let word = "café😀";
+        let utf8_len: usize = word.chars().map(|c| inspect_char(c).3).sum();
+        assert_eq!(utf8_len, word.len());
+        assert_eq!((word.len(), word.chars().count()), (9, 5));
+        assert_eq!(std::mem::size_of::<char>(), 4);
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(data in vec(any::<u8>(), 0..300)) {
+            let encoded = base64_encode(&data);
+            prop_assert_eq!(encoded.len(), data.len().div_ceil(3) * 4);
+            prop_assert_eq!(base64_decode(&encoded), Ok(data.clone()));
+
+            let pairs = rle_encode(&data);
+            prop_assert!(pairs.iter().all(|&(_, count)| count > 0));
+            // Two pairs with the same byte follow each other only when the first one is full.
+            prop_assert!(pairs
+                .windows(2)
+                .all(|w| w[0].0 != w[1].0 || w[0].1 == u8::MAX));
+            prop_assert_eq!(rle_decode(&pairs), data);
+        }
+
+        // Setting any of the unused low bits of the last character before the padding makes
+        // the encoding non canonical, and the decoder must reject it.
+        #[test]
+        fn base64_decode_rejects_trailing_bits(
+            data in vec(any::<u8>(), 1..300).prop_filter("needs padding", |d| d.len() % 3 != 0),
+            bits: u8,
+        ) {
+            let mut encoded = base64_encode(&data).into_bytes();
+            let padding = encoded.iter().rev().take_while(|&&b| b == b'=').count();
+            let unused = (1u32 << (2 * padding)) - 1;
+            let extra = bits as u32 & unused;
+            prop_assume!(extra != 0);
+            let last = encoded.len() - padding - 1;
+            let value = base64_value(encoded[last]).unwrap();
+            prop_assert_eq!(value & unused, 0);
+            encoded[last] = BASE64_ALPHABET[(value | extra) as usize];
+            let text = String::from_utf8(encoded).unwrap();
+            prop_assert_eq!(base64_decode(&text), Err(DecodeError::TrailingBits));
+        }
+
+        // Any string of alphabet characters and '=' decodes to Ok or Err without panicking.
+        #[test]
+        fn base64_decode_never_panics(text in "([A-Za-z0-9+/=]{4}){0,3}") {
+            if let Ok(bytes) = base64_decode(&text) {
+                prop_assert!(bytes.len() <= text.len() / 4 * 3);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_boundaries() {
+        // Padding boundaries: 1 and 2 leftover bytes get "==" and "=".
+        for byte in [0x00, 0xff] {
+            for (len, padding) in [(1, 2), (2, 1), (3, 0), (4, 2), (5, 1), (6, 0)] {
+                let data = vec![byte; len];
+                let encoded = base64_encode(&data);
+                assert_eq!(encoded.len() - encoded.trim_end_matches('=').len(), padding);
+                assert_eq!(base64_decode(&encoded), Ok(data));
+            }
+        }
+
+        // Run length boundaries.
+        for run in [254, 255, 256, 510, 511] {
+            let data = vec![b'x'; run];
+            let pairs = rle_encode(&data);
+            assert_eq!(pairs.len(), run.div_ceil(255));
+            assert_eq!(rle_decode(&pairs), data);
+        }
+        let mixed: Vec<u8> = [vec![1; 255], vec![2], vec![1; 256]].concat();
+        assert_eq!(rle_encode(&mixed), vec![(1, 255), (2, 1), (1, 255), (1, 1)]);
+    }
+}
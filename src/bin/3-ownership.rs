@@ -128,3 +128,230 @@
 // using it. The method Result::is_ok() return true if the operation succeeded.
 // The method Result::unwrap() return the value if the operation succeeded and panics if not.
 //
+// ------------ EXAMPLES -------------
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+// MOVE AND COPY
+// take_and_give_back takes the String by value: the ownership moves into the function and
+// comes back with the return value, the heap buffer is never copied (same pointer before and
+// after). After the call the value belongs to `back`, reading `s` would not compile:
+//
+//     let s = String::from("hello");
+//     let back = take_and_give_back(s);
+//     println!("{s}"); // error[E0382]: borrow of moved value: `s`
+//
+// because s and the parameter would both own the same buffer and both free it when dropped.
+// An i32 is Copy: passing it by value copies the bits and the original stays usable.
+fn take_and_give_back(s: String) -> String {
+    s
+}
+
+fn double(n: i32) -> i32 {
+    n * 2
+}
+
+// Builds a String, moves it into take_and_give_back and returns the String given back.
+fn demo_move() -> String {
+    let s = String::from("hello");
+    take_and_give_back(s)
+}
+
+fn demo_copy() -> (i32, i32) {
+    let n = 21;
+    let doubled = double(n);
+    (n, doubled)
+}
+
+fn move_copy_example() {
+    println!("move and copy: {:?} {:?}", demo_move(), demo_copy());
+}
+
+// STACK
+// A Vec wrapped so that only the top can be reached. push takes the value by value: the caller
+// gives up the ownership and the stack owns it from then on. pop moves it out again and the
+// caller becomes the owner, it can keep it after the stack is gone. peek only lends it: the
+// returned &T borrows the stack, so the stack can't be modified while the reference is used.
+struct Stack<T> {
+    items: Vec<T>,
+}
+
+impl<T> Stack<T> {
+    fn new() -> Self {
+        Stack { items: Vec::new() }
+    }
+
+    fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+}
+
+fn stack_example() {
+    let mut stack = Stack::new();
+    stack.push(String::from("first"));
+    stack.push(String::from("second"));
+    let top = stack.peek().cloned();
+    let popped = stack.pop();
+    println!(
+        "stack: peeked {top:?}, popped {popped:?}, {} left",
+        stack.len()
+    );
+}
+
+// SCOPE GUARD
+// The release on drop described at the top, used for any cleanup: the guard owns a closure and
+// calls it in its Drop implementation, so the cleanup runs when the guard goes out of scope
+// whichever way the scope is left (end of the block, early return, `?` or a panic).
+// The closure is kept in an Option because drop only gets &mut self: take() moves it out to
+// call it, since an FnOnce is consumed by the call. dismiss takes the guard by value and
+// empties the Option before the guard is dropped, so nothing runs.
+struct ScopeGuard<F: FnOnce()> {
+    cleanup: Option<F>,
+}
+
+impl<F: FnOnce()> ScopeGuard<F> {
+    fn new(cleanup: F) -> Self {
+        ScopeGuard {
+            cleanup: Some(cleanup),
+        }
+    }
+
+    fn dismiss(mut self) {
+        self.cleanup = None;
+    }
+}
+
+impl<F: FnOnce()> Drop for ScopeGuard<F> {
+    fn drop(&mut self) {
+        if let Some(cleanup) = self.cleanup.take() {
+            cleanup();
+        }
+    }
+}
+
+fn leave_early(flag: &Rc<Cell<bool>>, early: bool) -> u32 {
+    let flag = Rc::clone(flag);
+    let _guard = ScopeGuard::new(move || flag.set(true));
+    if early {
+        return 1;
+    }
+    2
+}
+
+fn scope_guard_example() {
+    let fired = Rc::new(Cell::new(false));
+    let returned = leave_early(&fired, true);
+    println!(
+        "scope guard: early return {returned}, cleanup fired {}",
+        fired.get()
+    );
+
+    let fired = Rc::new(Cell::new(false));
+    let flag = Rc::clone(&fired);
+    ScopeGuard::new(move || flag.set(true)).dismiss();
+    println!("scope guard: fired after dismiss {}", fired.get());
+}
+
+fn main() {
+    move_copy_example();
+    stack_example();
+    scope_guard_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn move_copy() {
+        assert_eq!(demo_move(), "hello");
+        assert_eq!(demo_copy(), (21, 42));
+
+        // Moving copies only the pointer, length and capacity, never the characters: the String
+        // given back still uses the buffer allocated at the start.
+        let s = String::from("hello");
+        let buffer = s.as_ptr();
+        let back = take_and_give_back(s);
+        assert_eq!(back.as_ptr(), buffer);
+    }
+
+    #[test]
+    fn stack() {
+        let mut stack = Stack::new();
+        let first = String::from("first");
+        stack.push(first);
+        // `first` has been moved into the stack and can't be used here anymore.
+        stack.push(String::from("second"));
+
+        assert_eq!(stack.peek().map(String::as_str), Some("second"));
+        assert_eq!(stack.len(), 2);
+
+        let mut owned = stack.pop().unwrap();
+        drop(stack);
+        // The popped String outlives the stack and can be modified by its new owner.
+        owned.push_str(" and last");
+        assert_eq!(owned, "second and last");
+
+        let mut stack = Stack::new();
+        assert_eq!(stack.peek(), None);
+        assert_eq!(stack.pop(), None);
+        stack.push(vec![1, 2, 3]);
+        let top = stack.peek().unwrap();
+        assert_eq!(top.len(), 3);
+        assert_eq!(stack.len(), 1);
+        assert_eq!(stack.pop(), Some(vec![1, 2, 3]));
+        assert_eq!(stack.len(), 0);
+    }
+
+    #[test]
+    fn scope_guard() {
+        let fired = Rc::new(Cell::new(false));
+        {
+            let flag = Rc::clone(&fired);
+            let _guard = ScopeGuard::new(move || flag.set(true));
+            assert!(!fired.get());
+        }
+        // The guard went out of scope at the closing brace.
+        assert!(fired.get());
+
+        let fired = Rc::new(Cell::new(false));
+        {
+            let flag = Rc::clone(&fired);
+            let guard = ScopeGuard::new(move || flag.set(true));
+            guard.dismiss();
+        }
+        assert!(!fired.get());
+        // The dismissed closure has been dropped together with its clone of the Rc.
+        assert_eq!(Rc::strong_count(&fired), 1);
+
+        let fired = Rc::new(Cell::new(false));
+        assert_eq!(leave_early(&fired, true), 1);
+        assert!(fired.get());
+        fired.set(false);
+        assert_eq!(leave_early(&fired, false), 2);
+        assert!(fired.get());
+
+        // Guards are dropped in the reverse order of their creation.
+        let order = Rc::new(RefCell::new(Vec::new()));
+        {
+            let (first, second) = (Rc::clone(&order), Rc::clone(&order));
+            let _a = ScopeGuard::new(move || first.borrow_mut().push("a"));
+            let _b = ScopeGuard::new(move || second.borrow_mut().push("b"));
+        }
+        assert_eq!(*order.borrow(), vec!["b", "a"]);
+    }
+}
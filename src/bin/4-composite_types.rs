@@ -189,3 +189,7 @@
 // Their representation in memory is equal to the largest field type size.
 // Unions can have methods in the same way enums do.
 // Litterally unions have same utility of enums while enum provide modern and better features.
+
+// This file only contains notes, main is empty so that the package builds: cargo build and
+// cargo test compile every binary in src/bin.
+fn main() {}
@@ -168,3 +168,515 @@
 // &mut self).
 // But it is possible to define an object-trait that implements multiple traits (they do not have
 // to be subtraits of each other tho).
+//
+// ---------------- Examples ----------------
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+
+// Plugin system
+//
+// The registry doesn't know the concrete types of the plugins, it stores them as object-traits
+// (Box<dyn Plugin>) so that types with different sizes can live in the same HashMap and
+// execute() is resolved at runtime through the vtable (dynamic invocation).
+trait Plugin {
+    fn name(&self) -> &str;
+    fn run(&self, input: &str) -> String;
+}
+
+struct Uppercase;
+
+impl Plugin for Uppercase {
+    fn name(&self) -> &str {
+        "uppercase"
+    }
+
+    fn run(&self, input: &str) -> String {
+        input.to_uppercase()
+    }
+}
+
+struct Repeat {
+    times: usize,
+}
+
+impl Plugin for Repeat {
+    fn name(&self) -> &str {
+        "repeat"
+    }
+
+    fn run(&self, input: &str) -> String {
+        input.repeat(self.times)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum PluginError {
+    Unknown(String),
+}
+
+struct PluginRegistry {
+    plugins: HashMap<String, Box<dyn Plugin>>,
+}
+
+impl PluginRegistry {
+    fn new() -> Self {
+        PluginRegistry {
+            plugins: HashMap::new(),
+        }
+    }
+
+    // A plugin registered with an already used name replaces the previous one.
+    fn register(&mut self, plugin: Box<dyn Plugin>) {
+        self.plugins.insert(plugin.name().to_string(), plugin);
+    }
+
+    fn execute(&self, name: &str, input: &str) -> Result<String, PluginError> {
+        self.plugins
+            .get(name)
+            .map(|plugin| plugin.run(input))
+            .ok_or_else(|| PluginError::Unknown(name.to_string()))
+    }
+}
+
+fn plugin_example() {
+    let mut registry = PluginRegistry::new();
+    registry.register(Box::new(Uppercase));
+    registry.register(Box::new(Repeat { times: 2 }));
+
+    println!("plugins: {:?}", registry.execute("uppercase", "plugin"));
+    println!("plugins: {:?}", registry.execute("repeat", "ab"));
+    println!("plugins: {:?}", registry.execute("reverse", "ab"));
+}
+
+// Serialization strategies
+//
+// The same behaviour (turning a value into a String) is implemented with different strategies.
+// Each strategy is a type implementing Format, it has no data and only a static function, and
+// Serialize is generic over the Format so a type implements it once for every possible format.
+// The format is chosen at compile time: Serialize::<JsonSerializer>::serialize(&record).
+enum Value {
+    Int(i64),
+    Text(String),
+}
+
+trait Format {
+    fn render(fields: &[(&str, Value)]) -> String;
+}
+
+struct JsonSerializer;
+
+impl Format for JsonSerializer {
+    fn render(fields: &[(&str, Value)]) -> String {
+        // serde_json quotes a string escaping quotes, backslashes and the control characters
+        // (\n, \t, \u0001...) that can't appear raw in a JSON string.
+        let quote = |s: &str| serde_json::to_string(s).unwrap();
+        let members: Vec<String> = fields
+            .iter()
+            .map(|(name, value)| match value {
+                Value::Int(n) => format!("{}:{}", quote(name), n),
+                Value::Text(s) => format!("{}:{}", quote(name), quote(s)),
+            })
+            .collect();
+        format!("{{{}}}", members.join(","))
+    }
+}
+
+struct CsvSerializer;
+
+impl Format for CsvSerializer {
+    fn render(fields: &[(&str, Value)]) -> String {
+        let header: Vec<&str> = fields.iter().map(|(name, _)| *name).collect();
+        let row: Vec<String> = fields
+            .iter()
+            .map(|(_, value)| match value {
+                Value::Int(n) => n.to_string(),
+                Value::Text(s) if s.contains([',', '"', '\n']) => {
+                    format!("\"{}\"", s.replace('"', "\"\""))
+                }
+                Value::Text(s) => s.clone(),
+            })
+            .collect();
+        format!("{}\n{}", header.join(","), row.join(","))
+    }
+}
+
+trait Serialize<F: Format> {
+    fn serialize(&self) -> String;
+}
+
+struct Record {
+    id: i64,
+    name: String,
+    city: String,
+}
+
+impl<F: Format> Serialize<F> for Record {
+    fn serialize(&self) -> String {
+        F::render(&[
+            ("id", Value::Int(self.id)),
+            ("name", Value::Text(self.name.clone())),
+            ("city", Value::Text(self.city.clone())),
+        ])
+    }
+}
+
+fn serialize_example() {
+    let record = Record {
+        id: 7,
+        name: "Ada \"The Countess\"".to_string(),
+        city: "London, UK".to_string(),
+    };
+    println!(
+        "serialize: {}",
+        Serialize::<JsonSerializer>::serialize(&record)
+    );
+    println!(
+        "serialize:\n{}",
+        Serialize::<CsvSerializer>::serialize(&record)
+    );
+}
+
+// Blanket implementations
+//
+// A blanket implementation implements a trait for every type satisfying some bounds instead of
+// for a single type: here every type implementing Display gets summarize() for free, this is the
+// same mechanism through which the standard library gives to_string() to every Display type.
+// The price is that no type implementing Display can have its own implementation of Summary,
+// the compiler would find two overlapping impls and refuse the code. Choosing the more specific
+// impl automatically is called specialization and is still unstable, on stable Rust a type that
+// wants a different summary has to change how it implements Display (or the blanket impl has
+// to be restricted to a narrower bound).
+trait Summary {
+    fn summarize(&self) -> String;
+}
+
+impl<T: Display> Summary for T {
+    fn summarize(&self) -> String {
+        let text = self.to_string();
+        match text.char_indices().nth(10) {
+            Some((cut, _)) => format!("{}...", &text[..cut]),
+            None => text,
+        }
+    }
+}
+
+struct Article {
+    title: String,
+    author: String,
+}
+
+impl Display for Article {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} by {}", self.title, self.author)
+    }
+}
+
+fn blanket_example() {
+    let article = Article {
+        title: "Traits".to_string(),
+        author: "Nunzio".to_string(),
+    };
+    println!(
+        "blanket impl: {}, {}, {}",
+        42.summarize(),
+        "a rather long sentence".summarize(),
+        article.summarize()
+    );
+}
+
+// Generic methods, associated types and where clauses together
+//
+// The associated type Input is fixed by each implementation (a Converter accepts only one type
+// of input) while the output O is a generic parameter of the method, so it is chosen by the
+// caller every time convert() is invoked, the only requirement is that O can be built from the
+// input. The where clause puts a bound on the associated type instead of on a generic parameter.
+trait Converter {
+    type Input;
+
+    fn convert<O: From<Self::Input>>(&self, input: Self::Input) -> O
+    where
+        Self::Input: Clone;
+}
+
+// Limits a reading of a sensor to a maximum value before handing it to other types.
+struct Clamp {
+    max: u16,
+}
+
+impl Converter for Clamp {
+    type Input = u16;
+
+    fn convert<O: From<u16>>(&self, input: u16) -> O
+    where
+        u16: Clone,
+    {
+        O::from(input.min(self.max))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Percent(f64);
+
+impl From<u16> for Percent {
+    fn from(value: u16) -> Self {
+        Percent(value as f64 / 10.0)
+    }
+}
+
+fn converter_example() {
+    let clamp = Clamp { max: 1000 };
+    let wide: u64 = clamp.convert(70);
+    let float: f64 = clamp.convert(5000);
+    println!(
+        "converter: {wide} {float} {:?}",
+        clamp.convert::<Percent>(425)
+    );
+}
+
+// impl Trait in argument and return position
+//
+// The same function can be written in four ways:
+// - impl Trait as argument is a shorter syntax for a generic parameter: apply and apply_generic
+//   are the same function, the compiler makes a copy of it for every closure type (static
+//   dispatch, calls can be inlined).
+// - impl Trait as return type hides the concrete type (a closure type has no name) but it's still
+//   a single type known by the compiler, so again static dispatch and no allocation.
+// - Box<dyn Fn> stores the closure on the heap and calls it through the vtable (dynamic dispatch).
+//   It costs an allocation and an indirect call but it's the only choice when a function has to
+//   return different closure types or when closures of different types go in the same Vec.
+fn apply(f: impl Fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn apply_generic<F: Fn(i32) -> i32>(f: F, x: i32) -> i32 {
+    f(x)
+}
+
+fn apply_dyn(f: &dyn Fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+fn make_incrementer() -> impl Fn(i32) -> i32 {
+    |x| x + 1
+}
+
+fn make_boxed_incrementer() -> Box<dyn Fn(i32) -> i32> {
+    Box::new(|x| x + 1)
+}
+
+fn make_step(by: i32) -> Box<dyn Fn(i32) -> i32> {
+    if by == 1 {
+        Box::new(|x| x + 1)
+    } else {
+        Box::new(move |x| x + by)
+    }
+}
+
+fn impl_trait_example() {
+    let x = 41;
+    println!(
+        "impl trait: increment of {x} is {} {} {} {} {}",
+        apply(make_incrementer(), x),
+        apply_generic(make_incrementer(), x),
+        apply_dyn(&make_incrementer(), x),
+        apply(make_boxed_incrementer(), x),
+        apply_dyn(&*make_boxed_incrementer(), x)
+    );
+    let steps: Vec<Box<dyn Fn(i32) -> i32>> = vec![make_step(1), make_step(10)];
+    let results: Vec<i32> = steps.iter().map(|step| step(0)).collect();
+    println!("impl trait: boxed steps from 0 {results:?}");
+}
+
+// Memoization
+//
+// Cached wraps a pure function and remembers its results. call() takes &self, like calling the
+// function would, so the HashMap is kept in a RefCell and borrowed mutably only for the time
+// needed to insert a result (interior mutability).
+// Cacheable is the behaviour "can be called with a K and gives back a V", implemented both by
+// the wrapper and by plain function pointers so code can accept either one.
+trait Cacheable<K, V> {
+    fn call(&self, key: K) -> V;
+}
+
+struct Cached<F, K, V> {
+    function: F,
+    cache: RefCell<HashMap<K, V>>,
+}
+
+impl<F, K, V> Cached<F, K, V>
+where
+    F: Fn(K) -> V,
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn new(function: F) -> Self {
+        Cached {
+            function,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl<F, K, V> Cacheable<K, V> for Cached<F, K, V>
+where
+    F: Fn(K) -> V,
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn call(&self, key: K) -> V {
+        if let Some(value) = self.cache.borrow().get(&key) {
+            return value.clone();
+        }
+        let value = (self.function)(key.clone());
+        self.cache.borrow_mut().insert(key, value.clone());
+        value
+    }
+}
+
+impl<K, V> Cacheable<K, V> for fn(K) -> V {
+    fn call(&self, key: K) -> V {
+        self(key)
+    }
+}
+
+fn sum_lengths<C: Cacheable<String, usize>>(c: &C, words: &[&str]) -> usize {
+    words.iter().map(|w| c.call(w.to_string())).sum()
+}
+
+fn cached_example() {
+    let runs = Cell::new(0);
+    let slow_len = Cached::new(|word: String| {
+        runs.set(runs.get() + 1);
+        word.chars().count()
+    });
+    let words = ["ciao", "rust", "ciao", "ciao", "trait"];
+    let total = sum_lengths(&slow_len, &words);
+    println!(
+        "cached: {total} characters, 5 calls, {} evaluations",
+        runs.get()
+    );
+
+    let plain: fn(String) -> usize = |word| word.len();
+    println!(
+        "cached: plain function pointer {}",
+        sum_lengths(&plain, &words)
+    );
+}
+
+fn main() {
+    plugin_example();
+    serialize_example();
+    blanket_example();
+    converter_example();
+    impl_trait_example();
+    cached_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugin() {
+        let mut registry = PluginRegistry::new();
+        registry.register(Box::new(Uppercase));
+        registry.register(Box::new(Repeat { times: 2 }));
+
+        assert_eq!(
+            registry.execute("uppercase", "rust"),
+            Ok("RUST".to_string())
+        );
+        assert_eq!(registry.execute("repeat", "ab"), Ok("abab".to_string()));
+        assert_eq!(
+            registry.execute("reverse", "ab"),
+            Err(PluginError::Unknown("reverse".to_string()))
+        );
+    }
+
+    #[test]
+    fn serialize() {
+        let record = Record {
+            id: 7,
+            name: "Ada \"The Countess\"".to_string(),
+            city: "London, UK".to_string(),
+        };
+        assert_eq!(
+            Serialize::<JsonSerializer>::serialize(&record),
+            r#"{"id":7,"name":"Ada \"The Countess\"","city":"London, UK"}"#
+        );
+        assert_eq!(
+            Serialize::<CsvSerializer>::serialize(&record),
+            "id,name,city\n7,\"Ada \"\"The Countess\"\"\",\"London, UK\""
+        );
+
+        let record = Record {
+            id: 8,
+            name: "two\nlines\tand a \u{1}".to_string(),
+            city: "C:\\".to_string(),
+        };
+        assert_eq!(
+            Serialize::<JsonSerializer>::serialize(&record),
+            r#"{"id":8,"name":"two\nlines\tand a \u0001","city":"C:\\"}"#
+        );
+    }
+
+    #[test]
+    fn blanket() {
+        assert_eq!(42.summarize(), "42");
+        assert_eq!('x'.summarize(), "x");
+        assert_eq!("a rather long sentence".summarize(), "a rather l...");
+        let article = Article {
+            title: "Traits".to_string(),
+            author: "Nunzio".to_string(),
+        };
+        assert_eq!(article.summarize(), "Traits by ...");
+    }
+
+    #[test]
+    fn converter() {
+        let clamp = Clamp { max: 1000 };
+        let wide: u64 = clamp.convert(70);
+        assert_eq!(wide, 70);
+        let float: f64 = clamp.convert(5000);
+        assert_eq!(float, 1000.0);
+        assert_eq!(clamp.convert::<Percent>(425), Percent(42.5));
+        assert_eq!(clamp.convert::<Percent>(u16::MAX), Percent(100.0));
+    }
+
+    #[test]
+    fn impl_trait() {
+        for x in [-5, 0, 41] {
+            let expected = x + 1;
+            assert_eq!(apply(make_incrementer(), x), expected);
+            assert_eq!(apply_generic(make_incrementer(), x), expected);
+            assert_eq!(apply_dyn(&make_incrementer(), x), expected);
+            assert_eq!(apply(make_boxed_incrementer(), x), expected);
+            assert_eq!(apply_dyn(&*make_boxed_incrementer(), x), expected);
+            assert_eq!(make_step(1)(x), expected);
+        }
+        let steps: Vec<Box<dyn Fn(i32) -> i32>> = vec![make_step(1), make_step(10)];
+        let results: Vec<i32> = steps.iter().map(|step| step(0)).collect();
+        assert_eq!(results, vec![1, 10]);
+    }
+
+    #[test]
+    fn cached() {
+        let runs = Cell::new(0);
+        let slow_len = Cached::new(|word: String| {
+            runs.set(runs.get() + 1);
+            word.chars().count()
+        });
+        let words = ["ciao", "rust", "ciao", "ciao", "trait"];
+        assert_eq!(sum_lengths(&slow_len, &words), 21);
+        // The function ran once for each of the 3 distinct words.
+        assert_eq!(runs.get(), 3);
+        assert_eq!(slow_len.call("rust".to_string()), 4);
+        assert_eq!(runs.get(), 3);
+
+        let plain: fn(String) -> usize = |word| word.len();
+        assert_eq!(sum_lengths(&plain, &words), 21);
+    }
+}
@@ -66,12 +66,12 @@
 // Since either the structs declarations should specify that the struct lives for the same time of
 // the fields the correct version keeping the rules for functions too is:
 //
-struct<'a, 'b> Point {
+struct Point<'a, 'b> {
     x: &'a i32,
-    y: &'b i32
+    y: &'b i32,
 }
 
-fn scale<'a, 'b, 'c>(r: &'c i32, p: Point<'a,'b>) -> i32 {
+fn scale<'a, 'b, 'c>(r: &'c i32, p: Point<'a, 'b>) -> i32 {
     r * (p.x * p.x + p.y * p.y)
 }
 //
@@ -97,12 +97,13 @@ fn scale<'a, 'b, 'c>(r: &'c i32, p: Point<'a,'b>) -> i32 {
 // itself a function.
 
 fn function(i: i32, d: f64) -> f64 {
-    return i as f64 * d
+    i as f64 * d
 }
 
-let ptr: fn(i32, f64) -> f64;
-ptr = function;
-ptr(10, 2.0);
+fn function_pointer() -> f64 {
+    let ptr: fn(i32, f64) -> f64 = function;
+    ptr(10, 2.0)
+}
 
 // In C++ is possible to add a third kind of usage of functions as superior order.
 // Is possible to allow classes to behave like functions, defining the function behaviour to the
@@ -147,3 +148,9 @@ ptr(10, 2.0);
 // 3. A closure that can take or borrow values must implement the Fn.
 //
 // Using the closures as illustrated is possible to implement functions or high order of any type.
+
+fn main() {
+    let (x, y) = (3, 4);
+    println!("scale: {}", scale(&2, Point { x: &x, y: &y }));
+    println!("function pointer: {}", function_pointer());
+}
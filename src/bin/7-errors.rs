@@ -91,3 +91,478 @@
 // using the with_context method providing a lambda function that takes as argument the runtime
 // value and returns a string while if the message error doesn't contain any runtime value can be
 // implemented with context method passing it directly the string to print.
+//
+// --------------- Examples ---------------
+//
+
+use esercizi_malnati::errors::{error_chain, parse_lines, LineError};
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::panic;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// CIRCUIT BREAKER
+// Retrying is a good recovery strategy for errors that go away by themselves, but if a service is
+// down every call keeps failing after waiting for its timeout and the retries only add load.
+// A circuit breaker counts the consecutive failures and after `threshold` of them it "opens":
+// for `cooldown` the calls fail immediately with BreakerError::Open without running at all.
+// When the cooldown has elapsed the breaker is half-open, the next call is a trial: if it
+// succeeds the breaker closes again, if it fails it opens for another cooldown.
+// The clock is a trait so that the test can move time forward without sleeping.
+trait Clock {
+    fn now(&self) -> Instant;
+}
+
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, PartialEq)]
+enum BreakerError<E> {
+    Open,
+    Inner(E),
+}
+
+struct CircuitBreaker<C: Clock> {
+    clock: C,
+    threshold: u32,
+    cooldown: Duration,
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl<C: Clock> CircuitBreaker<C> {
+    fn new(threshold: u32, cooldown: Duration, clock: C) -> Self {
+        CircuitBreaker {
+            clock,
+            threshold,
+            cooldown,
+            failures: 0,
+            opened_at: None,
+        }
+    }
+
+    fn state(&self) -> BreakerState {
+        match self.opened_at {
+            None => BreakerState::Closed,
+            Some(at) if self.clock.now() - at < self.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    fn call<T, E>(&mut self, f: impl FnOnce() -> Result<T, E>) -> Result<T, BreakerError<E>> {
+        if self.state() == BreakerState::Open {
+            return Err(BreakerError::Open);
+        }
+        match f() {
+            Ok(value) => {
+                self.failures = 0;
+                self.opened_at = None;
+                Ok(value)
+            }
+            Err(error) => {
+                self.failures += 1;
+                // A failed trial reopens immediately, otherwise wait for the threshold.
+                if self.opened_at.is_some() || self.failures >= self.threshold {
+                    self.opened_at = Some(self.clock.now());
+                }
+                Err(BreakerError::Inner(error))
+            }
+        }
+    }
+}
+
+fn circuit_breaker_example() {
+    let mut breaker = CircuitBreaker::new(1, Duration::from_secs(60), SystemClock);
+    println!(
+        "circuit breaker: {:?} -> {:?}",
+        breaker.state(),
+        breaker.call(|| Err::<u32, _>("service unavailable"))
+    );
+    // Open: the calls fail immediately and the function isn't run.
+    println!(
+        "circuit breaker: {:?} -> {:?}",
+        breaker.state(),
+        breaker.call(|| Ok::<u32, &str>(42))
+    );
+}
+
+// ERROR CHAIN
+// An error that is caused by another one can return it from source(), so a top level error
+// doesn't have to repeat in its message everything that went wrong below it.
+// Walking source() until it returns None gives the whole chain, from the outermost error to
+// the root cause (anyhow prints it in the same way with {:?}).
+// LineError, parse_lines and error_chain come from the errors module of the library.
+#[derive(Debug)]
+struct ConfigError {
+    path: String,
+    source: LineError,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot load config {}", self.path)
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn load_config(path: &str, text: &str) -> Result<Vec<i32>, ConfigError> {
+    parse_lines(text).map_err(|source| ConfigError {
+        path: path.to_string(),
+        source,
+    })
+}
+
+fn error_chain_example() {
+    println!("error chain: {:?}", parse_lines("1\n2\n3"));
+    let two = parse_lines("1\nx").unwrap_err();
+    println!("error chain: {}", error_chain(&two).join(": "));
+
+    let three = load_config("app.conf", "10\n20\n\n").unwrap_err();
+    let chain = error_chain(&three);
+    println!("error chain: {}", chain.join(": "));
+}
+
+// ACCUMULATING ERRORS
+// The ? operator stops at the first error, which is right when the next steps depend on the
+// previous ones, but when validating a form the user wants to see every wrong field at once.
+// In this case each check pushes its error into a Vec and the Err is returned only at the end.
+struct Form {
+    username: String,
+    email: String,
+    age: u32,
+    password: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum ValidationError {
+    EmptyUsername,
+    InvalidEmail(String),
+    AgeOutOfRange(u32),
+    PasswordTooShort { length: usize, min: usize },
+}
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+fn validate_form(form: &Form) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+    if form.username.trim().is_empty() {
+        errors.push(ValidationError::EmptyUsername);
+    }
+    match form.email.split_once('@') {
+        Some((user, domain)) if !user.is_empty() && domain.contains('.') => {}
+        _ => errors.push(ValidationError::InvalidEmail(form.email.clone())),
+    }
+    if !(13..=120).contains(&form.age) {
+        errors.push(ValidationError::AgeOutOfRange(form.age));
+    }
+    let length = form.password.chars().count();
+    if length < MIN_PASSWORD_LENGTH {
+        errors.push(ValidationError::PasswordTooShort {
+            length,
+            min: MIN_PASSWORD_LENGTH,
+        });
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn validation_example() {
+    let form = Form {
+        username: "  ".to_string(),
+        email: "nunzio.example.com".to_string(),
+        age: 30,
+        password: "short".to_string(),
+    };
+    let errors = validate_form(&form).unwrap_err();
+    println!(
+        "validation: {} errors reported at once {errors:?}",
+        errors.len()
+    );
+}
+
+// PANIC HOOK
+// A panic is an unrecoverable error, but the program can still observe it: the panic hook is
+// the function called with the message and the location before the stack is unwound, the
+// default one prints "thread 'main' panicked at ...".
+// Replacing it with set_hook lets us record the diagnostics somewhere else, for example to
+// send them to a log, while catch_unwind stops the unwinding so the thread keeps running.
+// The payload is the argument of panic!: a &str for a literal message, a String if formatted.
+fn install_diagnostic_hook() -> Arc<Mutex<Vec<String>>> {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&records);
+    panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "<non-string payload>".to_string()
+        };
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        sink.lock()
+            .unwrap()
+            .push(format!("{message} at {location}"));
+    }));
+    records
+}
+
+fn panic_hook_example() {
+    let records = install_diagnostic_hook();
+
+    let result = panic::catch_unwind(|| panic!("index {} out of bounds", 7));
+    println!("panic hook: caught the panic {}", result.is_err());
+
+    // Put back the default hook that prints to stderr.
+    let _ = panic::take_hook();
+
+    let records = records.lock().unwrap();
+    println!("panic hook: captured {:?}", records[0]);
+}
+
+// TRY_FOLD
+// try_fold is fold for closures that return a Result (or an Option): the accumulator is
+// passed on while they return Ok, the first Err stops the iteration and is returned.
+// Together with checked_mul, which returns None instead of overflowing, both a bad input and
+// an overflow are reported as errors without panicking.
+#[derive(Debug, PartialEq)]
+enum ProductError {
+    Parse { index: usize, source: ParseIntError },
+    Overflow { index: usize },
+}
+
+fn checked_product(values: &[&str]) -> Result<i64, ProductError> {
+    values
+        .iter()
+        .enumerate()
+        .try_fold(1i64, |acc, (index, value)| {
+            let n: i64 = value
+                .trim()
+                .parse()
+                .map_err(|source| ProductError::Parse { index, source })?;
+            acc.checked_mul(n).ok_or(ProductError::Overflow { index })
+        })
+}
+
+fn try_fold_example() {
+    println!(
+        "try_fold: product of 2 3 7 -1 = {:?}",
+        checked_product(&["2", "3", "7", "-1"])
+    );
+    println!(
+        "try_fold: product of 2 three x = {:?}",
+        checked_product(&["2", "three", "x"])
+    );
+    let max = i64::MAX.to_string();
+    println!(
+        "try_fold: product of 1 {max} 2 oops = {:?}",
+        checked_product(&["1", &max, "2", "oops"])
+    );
+}
+
+fn main() {
+    circuit_breaker_example();
+    error_chain_example();
+    validation_example();
+    panic_hook_example();
+    try_fold_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct ManualClock {
+        now: Cell<Instant>,
+    }
+
+    impl ManualClock {
+        fn advance(&self, by: Duration) {
+            self.now.set(self.now.get() + by);
+        }
+    }
+
+    impl Clock for &ManualClock {
+        fn now(&self) -> Instant {
+            self.now.get()
+        }
+    }
+
+    #[test]
+    fn circuit_breaker() {
+        let clock = ManualClock {
+            now: Cell::new(Instant::now()),
+        };
+        let mut breaker = CircuitBreaker::new(3, Duration::from_secs(10), &clock);
+        let runs = Cell::new(0);
+        let failing = || -> Result<u32, &str> {
+            runs.set(runs.get() + 1);
+            Err("service unavailable")
+        };
+        let working = || -> Result<u32, &str> {
+            runs.set(runs.get() + 1);
+            Ok(42)
+        };
+
+        // Closed: failures are returned until the threshold is reached, a success resets the count.
+        assert_eq!(
+            breaker.call(failing),
+            Err(BreakerError::Inner("service unavailable"))
+        );
+        assert_eq!(breaker.call(working), Ok(42));
+        for _ in 0..3 {
+            assert_eq!(breaker.state(), BreakerState::Closed);
+            assert!(breaker.call(failing).is_err());
+        }
+
+        // Open: the calls fail immediately and the function isn't run.
+        assert_eq!(breaker.state(), BreakerState::Open);
+        let before = runs.get();
+        assert_eq!(breaker.call(working), Err(BreakerError::Open));
+        assert_eq!(runs.get(), before);
+
+        // Half-open after the cooldown: a failing trial opens it again.
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert_eq!(
+            breaker.call(failing),
+            Err(BreakerError::Inner("service unavailable"))
+        );
+        assert_eq!(breaker.state(), BreakerState::Open);
+
+        // Half-open again: a successful trial closes it.
+        clock.advance(Duration::from_secs(11));
+        assert_eq!(breaker.state(), BreakerState::HalfOpen);
+        assert_eq!(breaker.call(working), Ok(42));
+        assert_eq!(breaker.state(), BreakerState::Closed);
+
+        let mut real = CircuitBreaker::new(1, Duration::from_secs(60), SystemClock);
+        assert!(real.call(failing).is_err());
+        assert_eq!(real.call(working), Err(BreakerError::Open));
+    }
+
+    #[test]
+    fn error_chain() {
+        assert_eq!(parse_lines("1\n2\n3").unwrap(), vec![1, 2, 3]);
+
+        let two = parse_lines("1\nx").unwrap_err();
+        assert_eq!(
+            super::error_chain(&two),
+            vec!["invalid number at line 2", "invalid digit found in string"]
+        );
+
+        let three = load_config("app.conf", "10\n20\n\n").unwrap_err();
+        let chain = super::error_chain(&three);
+        assert_eq!(chain.len(), 3);
+        assert_eq!(
+            chain,
+            vec![
+                "cannot load config app.conf",
+                "invalid number at line 3",
+                "cannot parse integer from empty string"
+            ]
+        );
+    }
+
+    #[test]
+    fn validation() {
+        let valid = Form {
+            username: "nunzio".to_string(),
+            email: "nunzio@example.com".to_string(),
+            age: 25,
+            password: "correct horse".to_string(),
+        };
+        assert_eq!(validate_form(&valid), Ok(()));
+
+        let one = Form { age: 7, ..valid };
+        assert_eq!(
+            validate_form(&one),
+            Err(vec![ValidationError::AgeOutOfRange(7)])
+        );
+
+        let many = Form {
+            username: "  ".to_string(),
+            email: "nunzio.example.com".to_string(),
+            age: 30,
+            password: "short".to_string(),
+        };
+        let errors = validate_form(&many).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::EmptyUsername,
+                ValidationError::InvalidEmail("nunzio.example.com".to_string()),
+                ValidationError::PasswordTooShort { length: 5, min: 8 },
+            ]
+        );
+    }
+
+    #[test]
+    fn panic_hook() {
+        let records = install_diagnostic_hook();
+
+        let result = panic::catch_unwind(|| panic!("index {} out of bounds", 7));
+        assert!(result.is_err());
+        let result = panic::catch_unwind(|| {
+            let v: Vec<u8> = Vec::new();
+            v.first().copied().expect("empty vector")
+        });
+        assert!(result.is_err());
+        assert_eq!(panic::catch_unwind(|| 1 + 1).unwrap(), 2);
+
+        // Put back the default hook that prints to stderr.
+        let _ = panic::take_hook();
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].starts_with("index 7 out of bounds at "));
+        assert!(records[0].contains("7-errors.rs"));
+        assert!(records[1].starts_with("empty vector at "));
+    }
+
+    #[test]
+    fn try_fold() {
+        assert_eq!(checked_product(&["2", "3", " 7", "-1"]), Ok(-42));
+        assert_eq!(checked_product(&[]), Ok(1));
+
+        match checked_product(&["2", "three", "x"]) {
+            Err(ProductError::Parse { index, source }) => {
+                assert_eq!(index, 1);
+                assert_eq!(source.to_string(), "invalid digit found in string");
+            }
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+
+        let max = i64::MAX.to_string();
+        assert_eq!(
+            checked_product(&["1", &max, "2", "oops"]),
+            Err(ProductError::Overflow { index: 2 })
+        );
+    }
+}
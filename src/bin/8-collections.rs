@@ -118,3 +118,795 @@
 // The type of the elements inserted must implement the trait Ord.
 //
 //
+// --------------- Examples ---------------
+//
+
+use esercizi_malnati::collections::{build_map, lookup_all, BitSet, SortedVec};
+use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::collections::BinaryHeap;
+use std::hash::BuildHasher;
+
+// --------------- EventLog ---------------
+//
+// Event sourcing stores the list of the changes (events) instead of the current state: the
+// state is derived folding the events in order starting from an initial value.
+// Replaying a long Vec every time is slow, so every `snapshot_every` events the log stores the
+// state reached so far and replay() starts from the last snapshot folding only the events
+// appended after it. full_replay() ignores the snapshot and is used to check they agree.
+type Fold<E, S> = Box<dyn Fn(S, &E) -> S>;
+
+struct EventLog<E, S> {
+    events: Vec<E>,
+    initial: S,
+    fold: Fold<E, S>,
+    snapshot_every: usize,
+    snapshot: Option<(usize, S)>,
+}
+
+impl<E, S: Clone> EventLog<E, S> {
+    fn new(initial: S, snapshot_every: usize, fold: impl Fn(S, &E) -> S + 'static) -> Self {
+        EventLog {
+            events: Vec::new(),
+            initial,
+            fold: Box::new(fold),
+            snapshot_every: snapshot_every.max(1),
+            snapshot: None,
+        }
+    }
+
+    fn append(&mut self, event: E) {
+        self.events.push(event);
+        if self.events.len().is_multiple_of(self.snapshot_every) {
+            self.snapshot();
+        }
+    }
+
+    fn snapshot(&mut self) {
+        let state = self.replay();
+        self.snapshot = Some((self.events.len(), state));
+    }
+
+    fn replay(&self) -> S {
+        let (start, state) = match &self.snapshot {
+            Some((len, state)) => (*len, state.clone()),
+            None => (0, self.initial.clone()),
+        };
+        self.events[start..]
+            .iter()
+            .fold(state, |state, event| (self.fold)(state, event))
+    }
+
+    fn full_replay(&self) -> S {
+        self.events
+            .iter()
+            .fold(self.initial.clone(), |state, event| {
+                (self.fold)(state, event)
+            })
+    }
+
+    fn snapshot_len(&self) -> usize {
+        self.snapshot.as_ref().map_or(0, |(len, _)| *len)
+    }
+}
+
+enum AccountEvent {
+    Deposit(u64),
+    Withdraw(u64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Account {
+    balance: u64,
+    operations: usize,
+}
+
+// An empty log of account events with a snapshot every 4 of them.
+fn account_log() -> EventLog<AccountEvent, Account> {
+    EventLog::new(
+        Account {
+            balance: 0,
+            operations: 0,
+        },
+        4,
+        |account: Account, event: &AccountEvent| Account {
+            balance: match event {
+                AccountEvent::Deposit(n) => account.balance + n,
+                AccountEvent::Withdraw(n) => account.balance.saturating_sub(*n),
+            },
+            operations: account.operations + 1,
+        },
+    )
+}
+
+fn event_log_example() {
+    let mut log = account_log();
+    for i in 1..=5 {
+        log.append(AccountEvent::Deposit(i * 10));
+    }
+    log.append(AccountEvent::Withdraw(5));
+    println!(
+        "event log: {:?} after {} events, snapshot at {}, same as the full replay {}",
+        log.replay(),
+        log.events.len(),
+        log.snapshot_len(),
+        log.replay() == log.full_replay()
+    );
+}
+
+fn bitset_example() {
+    let (mut a, mut b) = (BitSet::new(), BitSet::new());
+    [0, 63, 64, 500].into_iter().for_each(|i| a.set(i));
+    [1, 63, 64, 129].into_iter().for_each(|i| b.set(i));
+    let mut intersection = a.clone();
+    intersection.intersect_with(&b);
+    a.union_with(&b);
+    println!(
+        "bitset: union {:?} in {} words, intersection {:?}",
+        a.iter().collect::<Vec<_>>(),
+        a.words().len(),
+        intersection.iter().collect::<Vec<_>>()
+    );
+}
+
+fn sorted_vec_example() {
+    let mut sorted = SortedVec::new();
+    for value in [5, 1, 4, 1, 3] {
+        sorted.insert(value);
+    }
+    let removed = sorted.remove(&1);
+    println!(
+        "sorted vec: {:?} after removing {removed:?}, contains 4 {}",
+        sorted.as_slice(),
+        sorted.contains(&4)
+    );
+}
+
+// --------------- FrozenMap ---------------
+//
+// A map built once and then only read: the pairs are sorted by key in a single Vec and get()
+// is a binary search. There is no hashing and the entries are contiguous in memory, so for
+// read-many data (configuration, lookup tables) it is compact and cache friendly.
+// No method takes &mut self, so after construction the content can't change.
+// Two pairs with the same key make the construction fail instead of silently keeping one.
+#[derive(Debug)]
+struct FrozenMap<K: Ord, V> {
+    entries: Vec<(K, V)>,
+}
+
+#[derive(Debug, PartialEq)]
+struct DuplicateKey<K>(K);
+
+impl<K: Ord, V> FrozenMap<K, V> {
+    fn from_pairs(mut pairs: Vec<(K, V)>) -> Result<Self, DuplicateKey<K>> {
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        if let Some(index) = pairs.windows(2).position(|w| w[0].0 == w[1].0) {
+            return Err(DuplicateKey(pairs.swap_remove(index).0));
+        }
+        Ok(FrozenMap { entries: pairs })
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let index = self.entries.binary_search_by(|(k, _)| k.cmp(key)).ok()?;
+        Some(&self.entries[index].1)
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+fn frozen_map_example() {
+    let frozen = FrozenMap::from_pairs(vec![("c", 3), ("a", 1), ("b", 2)]).unwrap();
+    println!(
+        "frozen map: {} keys, b -> {:?}, z -> {:?}",
+        frozen.len(),
+        frozen.get(&"b"),
+        frozen.get(&"z")
+    );
+    let duplicated = vec![(3, "c"), (1, "a"), (1, "again")];
+    println!(
+        "frozen map: {:?}",
+        FrozenMap::from_pairs(duplicated).unwrap_err()
+    );
+}
+
+// --------------- Matrix ---------------
+//
+// A 2D grid stored row-major in a single Vec: the element (r, c) is at index r * cols + c.
+// One allocation for the whole matrix instead of a Vec<Vec<T>> with one per row, and a row is
+// a contiguous slice. A column is every cols-th element starting from c, so the iterator is
+// skip(c).step_by(cols).
+// get returns None outside of the matrix, set, row and col panic like Vec indexing does.
+#[derive(Debug, Clone, PartialEq)]
+struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Clone> Matrix<T> {
+    fn new(rows: usize, cols: usize, fill: T) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![fill; rows * cols],
+        }
+    }
+}
+
+impl<T> Matrix<T> {
+    fn get(&self, r: usize, c: usize) -> Option<&T> {
+        if r < self.rows && c < self.cols {
+            self.data.get(r * self.cols + c)
+        } else {
+            None
+        }
+    }
+
+    fn set(&mut self, r: usize, c: usize, value: T) {
+        assert!(
+            r < self.rows && c < self.cols,
+            "matrix index ({r}, {c}) out of bounds for {}x{}",
+            self.rows,
+            self.cols
+        );
+        self.data[r * self.cols + c] = value;
+    }
+
+    fn row(&self, r: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            r < self.rows,
+            "row {r} out of bounds for {} rows",
+            self.rows
+        );
+        self.data[r * self.cols..(r + 1) * self.cols].iter()
+    }
+
+    fn col(&self, c: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            c < self.cols,
+            "column {c} out of bounds for {} columns",
+            self.cols
+        );
+        self.data.iter().skip(c).step_by(self.cols)
+    }
+}
+
+// A 3x3 matrix holding 1..=9 row by row.
+fn one_to_nine() -> Matrix<usize> {
+    let mut m = Matrix::new(3, 3, 0);
+    for r in 0..3 {
+        for c in 0..3 {
+            m.set(r, c, r * 3 + c + 1);
+        }
+    }
+    m
+}
+
+fn matrix_example() {
+    let m = one_to_nine();
+    let trace: usize = (0..3).filter_map(|i| m.get(i, i)).sum();
+    println!(
+        "matrix: row 0 {:?}, column 2 {:?}, diagonal sum of 1..=9 is {trace}",
+        m.row(0).collect::<Vec<_>>(),
+        m.col(2).collect::<Vec<_>>()
+    );
+}
+
+// --------------- Shuffle and sample ---------------
+//
+// Fisher-Yates shuffle: going from the last position down, each element is swapped with a
+// random one among those not placed yet, every permutation has the same probability.
+// The random numbers come from a xorshift generator: with the same seed the sequence, and so
+// the permutation, is always the same, which makes the result reproducible.
+// sample runs only the first k steps of the shuffle on the indices, so the k picked indices
+// are distinct and the data isn't modified.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift.
+        XorShift(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+fn shuffle<T>(data: &mut [T], seed: u64) {
+    let mut rng = XorShift::new(seed);
+    for i in (1..data.len()).rev() {
+        data.swap(i, rng.below(i + 1));
+    }
+}
+
+fn sample_indices(len: usize, k: usize, seed: u64) -> Vec<usize> {
+    let mut rng = XorShift::new(seed);
+    let mut indices: Vec<usize> = (0..len).collect();
+    let k = k.min(len);
+    for i in 0..k {
+        let j = i + rng.below(len - i);
+        indices.swap(i, j);
+    }
+    indices.truncate(k);
+    indices
+}
+
+fn sample<T: Clone>(data: &[T], k: usize, seed: u64) -> Vec<T> {
+    sample_indices(data.len(), k, seed)
+        .into_iter()
+        .map(|i| data[i].clone())
+        .collect()
+}
+
+fn shuffle_example() {
+    let mut numbers: Vec<u32> = (0..10).collect();
+    shuffle(&mut numbers, 7);
+    println!(
+        "shuffle: {numbers:?} sample: {:?} indices: {:?}",
+        sample(&["a", "b", "c", "d", "e"], 3, 42),
+        sample_indices(100, 5, 3)
+    );
+}
+
+// --------------- Group consecutive ---------------
+//
+// Run length encoding of any slice: each run of consecutive equal elements becomes a pair
+// (value, length). Only the last pair is compared with the next element, so equal values in
+// different runs stay in different pairs.
+fn group_consecutive<T: PartialEq + Clone>(data: &[T]) -> Vec<(T, usize)> {
+    let mut groups: Vec<(T, usize)> = Vec::new();
+    for item in data {
+        match groups.last_mut() {
+            Some((value, count)) if value == item => *count += 1,
+            _ => groups.push((item.clone(), 1)),
+        }
+    }
+    groups
+}
+
+fn group_consecutive_example() {
+    let chars: Vec<char> = "aaabccdaa".chars().collect();
+    let groups = group_consecutive(&chars);
+    println!("group consecutive: aaabccdaa -> {groups:?}");
+}
+
+// --------------- MedianTracker ---------------
+//
+// Running median of a stream with two BinaryHeaps: `lower` is a max-heap with the smaller
+// half of the values, `upper` a min-heap (Reverse) with the bigger half. They are kept
+// balanced so that lower has the same number of elements as upper or one more: the median is
+// then the top of lower, or the mean of the two tops when the count is even.
+// add is O(log(n)) and median is O(1), instead of sorting all the values every time.
+struct MedianTracker {
+    lower: BinaryHeap<i64>,
+    upper: BinaryHeap<Reverse<i64>>,
+}
+
+impl MedianTracker {
+    fn new() -> Self {
+        MedianTracker {
+            lower: BinaryHeap::new(),
+            upper: BinaryHeap::new(),
+        }
+    }
+
+    fn add(&mut self, value: i64) {
+        match self.lower.peek() {
+            Some(&top) if value > top => self.upper.push(Reverse(value)),
+            _ => self.lower.push(value),
+        }
+        if self.lower.len() > self.upper.len() + 1 {
+            let moved = self.lower.pop().unwrap();
+            self.upper.push(Reverse(moved));
+        } else if self.upper.len() > self.lower.len() {
+            let Reverse(moved) = self.upper.pop().unwrap();
+            self.lower.push(moved);
+        }
+    }
+
+    fn median(&self) -> Option<f64> {
+        let low = *self.lower.peek()? as f64;
+        if self.lower.len() > self.upper.len() {
+            Some(low)
+        } else {
+            let Reverse(high) = self.upper.peek()?;
+            Some((low + *high as f64) / 2.0)
+        }
+    }
+}
+
+fn median_tracker_example() {
+    let mut tracker = MedianTracker::new();
+    for value in [5, -3, 8, 8, -10, 2, 7] {
+        tracker.add(value);
+        println!(
+            "median tracker: added {value}, median {:?}",
+            tracker.median()
+        );
+    }
+}
+
+// --------------- Panic conditions ---------------
+//
+// The misuses that panic instead of returning an error. Each one has a test marked
+// #[should_panic(expected = "...")]: the test passes only if it panics with a message
+// containing the expected text, so the tests document the exact messages of set, row and col.
+// The Vec messages are not checked, they belong to std and can change between versions.
+fn panic_conditions_example() {
+    // The non panicking counterparts of the accesses that panic.
+    let m = Matrix::new(2, 3, 0);
+    println!(
+        "panic conditions: get(2, 0) on a 2x3 matrix is {:?} where set(2, 0, _) panics",
+        m.get(2, 0)
+    );
+}
+
+// --------------- Hashers ---------------
+//
+// A HashMap is generic over the BuildHasher that creates the hasher for every key. The default
+// RandomState uses SipHash with random keys chosen when the map is created: the hashes can't
+// be predicted, so an attacker can't send keys that all land in the same bucket (HashDoS), at
+// the price of a slower hash. FNV is a few multiplications and xors per byte, fast on short
+// keys but always the same, aHash uses AES instructions when the CPU has them and is seeded
+// like SipHash. The hasher only changes where the entries are stored, never what a lookup
+// returns: the example checks the same lookups on all of them, benches/hashers.rs measures
+// the difference in speed. build_map and lookup_all are in the collections module of the
+// library, so the benchmark runs the same code.
+// Run with: cargo run --bin 8-collections --features ahash
+fn hashers_example() {
+    let keys: Vec<String> = (0..100).map(|i| format!("key-{i}")).collect();
+    let probes: Vec<String> = (0..200).step_by(7).map(|i| format!("key-{i}")).collect();
+
+    let sip = lookup_all(&build_map::<RandomState>(&keys), &probes);
+    let fnv = lookup_all(&build_map::<fnv::FnvBuildHasher>(&keys), &probes);
+    println!(
+        "hashers: {} lookups, {} found, SipHash and FNV agree {}",
+        probes.len(),
+        sip.iter().flatten().count(),
+        sip == fnv
+    );
+    #[cfg(feature = "ahash")]
+    println!(
+        "hashers: SipHash and aHash agree {}",
+        sip == lookup_all(&build_map::<ahash::RandomState>(&keys), &probes)
+    );
+    // Two RandomStates hash the same key differently, FNV always gives the same hash.
+    let (a, b) = (RandomState::new(), RandomState::new());
+    let fnv_hasher = fnv::FnvBuildHasher::default();
+    println!(
+        "hashers: key-0 with two RandomStates {:x} {:x}, with FNV {:x}",
+        a.hash_one("key-0"),
+        b.hash_one("key-0"),
+        fnv_hasher.hash_one("key-0")
+    );
+}
+
+fn main() {
+    event_log_example();
+    bitset_example();
+    sorted_vec_example();
+    frozen_map_example();
+    matrix_example();
+    shuffle_example();
+    group_consecutive_example();
+    median_tracker_example();
+    panic_conditions_example();
+    hashers_example();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn event_log() {
+        let mut log = account_log();
+        assert_eq!(log.replay().balance, 0);
+
+        for i in 1..=10 {
+            log.append(AccountEvent::Deposit(i * 10));
+            if i % 3 == 0 {
+                log.append(AccountEvent::Withdraw(5));
+            }
+            // Every append is visible in the derived state.
+            assert_eq!(log.replay(), log.full_replay());
+            assert_eq!(log.replay().operations, log.events.len());
+        }
+        assert_eq!(log.events.len(), 13);
+        assert_eq!(log.snapshot_len(), 12);
+        assert_eq!(log.replay().balance, 550 - 15);
+
+        log.snapshot();
+        assert_eq!(log.snapshot_len(), 13);
+        assert_eq!(log.replay(), log.full_replay());
+    }
+
+    #[test]
+    fn bitset() {
+        let mut a = BitSet::new();
+        for i in [0, 63, 64, 127, 128, 500] {
+            a.set(i);
+        }
+        assert!(a.contains(63) && a.contains(64) && a.contains(500));
+        assert!(!a.contains(62) && !a.contains(65) && !a.contains(10_000));
+        assert_eq!(a.words().len(), 8);
+        assert_eq!(a.count_ones(), 6);
+        a.set(64);
+        assert_eq!(a.count_ones(), 6);
+        a.clear(64);
+        a.clear(10_000);
+        assert!(!a.contains(64));
+        assert_eq!(a.count_ones(), 5);
+
+        let mut b = BitSet::new();
+        for i in [1, 63, 128, 129] {
+            b.set(i);
+        }
+        let mut union = a.clone();
+        union.union_with(&b);
+        assert_eq!(
+            union.iter().collect::<Vec<_>>(),
+            vec![0, 1, 63, 127, 128, 129, 500]
+        );
+        let mut intersection = a.clone();
+        intersection.intersect_with(&b);
+        assert_eq!(intersection.iter().collect::<Vec<_>>(), vec![63, 128]);
+        assert_eq!(intersection.count_ones(), 2);
+
+        let mut evens = BitSet::new();
+        (0..1000).step_by(2).for_each(|i| evens.set(i));
+        assert_eq!(evens.count_ones(), 500);
+
+        // Equal elements, equal sets: the words left empty aren't kept.
+        let mut cleared = BitSet::new();
+        cleared.set(200);
+        cleared.clear(200);
+        assert_eq!(cleared, BitSet::new());
+        let mut disjoint = BitSet::new();
+        disjoint.set(500);
+        disjoint.intersect_with(&b);
+        assert_eq!(disjoint, BitSet::new());
+    }
+
+    #[test]
+    fn sorted_vec() {
+        let mut sorted = SortedVec::new();
+        let mut inserted = Vec::new();
+        for i in 0..500u32 {
+            // A permutation-like sequence with repetitions, not inserted in order.
+            let value = (i * 7919) % 331;
+            sorted.insert(value);
+            inserted.push(value);
+            assert!(sorted.as_slice().windows(2).all(|w| w[0] <= w[1]));
+        }
+        assert_eq!(sorted.len(), 500);
+        for probe in 0..400 {
+            assert_eq!(sorted.contains(&probe), inserted.contains(&probe));
+        }
+
+        assert_eq!(sorted.remove(&1000), None);
+        let removed = sorted.remove(&inserted[0]);
+        assert_eq!(removed, Some(inserted[0]));
+        assert_eq!(sorted.len(), 499);
+
+        let ascending: Vec<u32> = sorted.iter().copied().collect();
+        let mut expected = inserted.clone();
+        expected.sort();
+        expected.remove(expected.binary_search(&inserted[0]).unwrap());
+        assert_eq!(ascending, expected);
+    }
+
+    #[test]
+    fn frozen_map() {
+        let pairs: Vec<(String, usize)> = (0..200)
+            .map(|i| (format!("key{}", (i * 37) % 200), i))
+            .collect();
+        let reference: HashMap<String, usize> = pairs.iter().cloned().collect();
+        let frozen = FrozenMap::from_pairs(pairs).unwrap();
+        assert_eq!(frozen.len(), reference.len());
+        for i in 0..250 {
+            let key = format!("key{i}");
+            assert_eq!(frozen.get(&key), reference.get(&key));
+        }
+
+        let duplicated = vec![(3, "c"), (1, "a"), (2, "b"), (1, "again")];
+        assert_eq!(
+            FrozenMap::from_pairs(duplicated).unwrap_err(),
+            DuplicateKey(1)
+        );
+        let empty: FrozenMap<u8, ()> = FrozenMap::from_pairs(Vec::new()).unwrap();
+        assert_eq!(empty.get(&0), None);
+    }
+
+    #[test]
+    fn matrix() {
+        let m = one_to_nine();
+        assert_eq!(m.row(0).copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(m.row(2).copied().collect::<Vec<_>>(), vec![7, 8, 9]);
+        assert_eq!(m.col(0).copied().collect::<Vec<_>>(), vec![1, 4, 7]);
+        assert_eq!(m.col(2).copied().collect::<Vec<_>>(), vec![3, 6, 9]);
+        let trace: usize = (0..3).filter_map(|i| m.get(i, i)).sum();
+        assert_eq!(trace, 15);
+
+        assert_eq!(m.get(1, 1), Some(&5));
+        assert_eq!(m.get(3, 0), None);
+        assert_eq!(m.get(0, 3), None);
+        let mut rect = Matrix::new(2, 4, '.');
+        rect.set(1, 3, '#');
+        assert_eq!(rect.get(1, 3), Some(&'#'));
+        assert_eq!(rect.get(3, 1), None);
+        assert_eq!(rect.col(3).collect::<String>(), ".#");
+    }
+
+    #[test]
+    fn shuffle_works() {
+        let original: Vec<u32> = (0..20).chain(0..5).collect();
+        let mut a = original.clone();
+        shuffle(&mut a, 7);
+        assert_ne!(a, original);
+        let mut c = original.clone();
+        shuffle(&mut c, 8);
+        assert_ne!(a, c);
+
+        let mut empty: [u8; 0] = [];
+        shuffle(&mut empty, 1);
+
+        let words = ["a", "b", "c", "d", "e"];
+        assert_eq!(sample(&words, 3, 42).len(), 3);
+        assert_eq!(sample(&words, 10, 42).len(), 5);
+    }
+
+    proptest! {
+        // The same seed gives the same permutation, and it is a permutation: sorting the
+        // shuffled copy gives back the sorted original.
+        #[test]
+        fn shuffle_is_a_reproducible_permutation(original in vec(any::<u32>(), 0..50), seed: u64) {
+            let mut a = original.clone();
+            let mut b = original.clone();
+            shuffle(&mut a, seed);
+            shuffle(&mut b, seed);
+            prop_assert_eq!(&a, &b);
+            let mut sorted_original = original;
+            a.sort();
+            sorted_original.sort();
+            prop_assert_eq!(a, sorted_original);
+        }
+
+        #[test]
+        fn sample_indices_are_distinct(len in 0..200usize, k in 0..250usize, seed: u64) {
+            let indices = sample_indices(len, k, seed);
+            prop_assert_eq!(indices.len(), k.min(len));
+            prop_assert!(indices.iter().all(|&i| i < len));
+            let mut distinct = indices.clone();
+            distinct.sort();
+            distinct.dedup();
+            prop_assert_eq!(distinct.len(), indices.len());
+            prop_assert_eq!(sample_indices(len, k, seed), indices);
+        }
+    }
+
+    #[test]
+    fn group_consecutive_works() {
+        let empty: [char; 0] = [];
+        assert_eq!(group_consecutive(&empty), vec![]);
+        assert_eq!(group_consecutive(&[4, 4, 4, 4]), vec![(4, 4)]);
+        assert_eq!(
+            group_consecutive(&["a", "b", "c"]),
+            vec![("a", 1), ("b", 1), ("c", 1)]
+        );
+        let chars: Vec<char> = "aaabccdaa".chars().collect();
+        let groups = group_consecutive(&chars);
+        assert_eq!(
+            groups,
+            vec![('a', 3), ('b', 1), ('c', 2), ('d', 1), ('a', 2)]
+        );
+        assert_eq!(groups.iter().map(|(_, n)| n).sum::<usize>(), chars.len());
+    }
+
+    #[test]
+    fn median_tracker() {
+        let mut tracker = MedianTracker::new();
+        assert_eq!(tracker.median(), None);
+        let values = [5, -3, 8, 8, -10, 2, 7];
+        let expected = [5.0, 1.0, 5.0, 6.5, 5.0, 3.5, 5.0];
+        for (value, median) in values.into_iter().zip(expected) {
+            tracker.add(value);
+            assert_eq!(tracker.median(), Some(median));
+        }
+
+        // Cross check with sorting on a longer stream.
+        let mut tracker = MedianTracker::new();
+        let mut seen = Vec::new();
+        for i in 0..200i64 {
+            let value = (i * 7919) % 201 - 100;
+            tracker.add(value);
+            seen.push(value);
+            seen.sort();
+            let n = seen.len();
+            let median = if n % 2 == 1 {
+                seen[n / 2] as f64
+            } else {
+                (seen[n / 2 - 1] + seen[n / 2]) as f64 / 2.0
+            };
+            assert_eq!(tracker.median(), Some(median));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "matrix index (2, 0) out of bounds for 2x3")]
+    fn matrix_set_past_the_last_row() {
+        Matrix::new(2, 3, 0).set(2, 0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "matrix index (0, 3) out of bounds for 2x3")]
+    fn matrix_set_past_the_last_column() {
+        Matrix::new(2, 3, 0).set(0, 3, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "matrix index (0, 0) out of bounds for 0x0")]
+    fn matrix_set_on_an_empty_matrix() {
+        Matrix::new(0, 0, 0).set(0, 0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 2 out of bounds for 2 rows")]
+    fn matrix_row_out_of_bounds() {
+        Matrix::new(2, 3, 0).row(2).count();
+    }
+
+    #[test]
+    #[should_panic(expected = "column 5 out of bounds for 3 columns")]
+    fn matrix_col_out_of_bounds() {
+        Matrix::new(2, 3, 0).col(5).count();
+    }
+
+    #[test]
+    fn matrix_get_out_of_bounds() {
+        // The non panicking counterpart of the same accesses.
+        let m = Matrix::new(2, 3, 0);
+        assert_eq!(m.get(2, 0), None);
+        assert_eq!(m.get(0, 3), None);
+        assert_eq!(Matrix::new(0, 0, 0).get(0, 0), None);
+    }
+
+    #[test]
+    fn hashers() {
+        let keys: Vec<String> = (0..10_000).map(|i| format!("key-{i}")).collect();
+        let probes: Vec<String> = (0..20_000).step_by(7).map(|i| format!("key-{i}")).collect();
+
+        let sip = lookup_all(&build_map::<RandomState>(&keys), &probes);
+        let fnv = lookup_all(&build_map::<fnv::FnvBuildHasher>(&keys), &probes);
+        assert_eq!(sip, fnv);
+        #[cfg(feature = "ahash")]
+        assert_eq!(
+            sip,
+            lookup_all(&build_map::<ahash::RandomState>(&keys), &probes)
+        );
+        assert_eq!(sip[1], Some(7));
+        assert_eq!(sip.iter().flatten().count(), 10_000usize.div_ceil(7));
+
+        // Two RandomStates hash the same key differently, FNV always gives the same hash.
+        let (a, b) = (RandomState::new(), RandomState::new());
+        assert_ne!(a.hash_one("key-0"), b.hash_one("key-0"));
+        let fnv_hasher = fnv::FnvBuildHasher::default();
+        assert_eq!(
+            fnv_hasher.hash_one("key-0"),
+            fnv::FnvBuildHasher::default().hash_one("key-0")
+        );
+    }
+}
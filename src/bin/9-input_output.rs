@@ -149,3 +149,543 @@
 // 
 // Most of the BufReader and BufWriter types in the standard library implement Seek.
 //
+// ---------------------- Examples ----------------------
+//
+// The examples that touch the file system work inside a fresh folder in the temporary directory
+// of the OS which main deletes at the end, the tests get their own from tempfile::tempdir().
+
+use std::fs::{self, File, FileTimes};
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// GLOB MATCHING
+//
+// '?' matches any single character and '*' any sequence of characters (also empty).
+// The matching goes forward on both strings and when a '*' is found it remembers where it was:
+// if later the characters stop matching it goes back to that point and lets the '*' swallow one
+// more character of the name. Only the last '*' needs to be remembered so the whole check is a
+// single loop without recursion.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// Entries of `dir` (not recursive) whose file name matches the pattern, sorted by path since
+// read_dir gives no guarantee on the order.
+fn list_matching(dir: &Path, pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let mut matching = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if glob_match(pattern, &entry.file_name().to_string_lossy()) {
+            matching.push(entry.path());
+        }
+    }
+    matching.sort();
+    Ok(matching)
+}
+
+fn glob_example(root: &Path) -> io::Result<()> {
+    let dir = root.join("glob");
+    fs::create_dir(&dir)?;
+    for name in ["a.txt", "b.txt", "file1.rs", "file22.rs", "readme.md"] {
+        fs::write(dir.join(name), name)?;
+    }
+    for pattern in ["*.txt", "file?.rs", "*"] {
+        let names: Vec<String> = list_matching(&dir, pattern)?
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        println!("glob: {pattern} matches {names:?}");
+    }
+    Ok(())
+}
+
+// POLLING WATCHER
+//
+// Without OS specific APIs (inotify, FSEvents, ReadDirectoryChangesW) the portable way to notice
+// that a file changed is to read its metadata periodically and compare the last modification
+// time with the previous one. The first reading is the reference and isn't a change.
+// The drawback is that two modifications within the same poll interval are seen as one.
+struct ModificationWatcher<'a> {
+    path: &'a Path,
+    last: SystemTime,
+}
+
+impl<'a> ModificationWatcher<'a> {
+    fn new(path: &'a Path) -> io::Result<Self> {
+        let last = fs::metadata(path)?.modified()?;
+        Ok(ModificationWatcher { path, last })
+    }
+
+    // Reads the modification time again and returns it if it changed since the last poll.
+    fn poll(&mut self) -> io::Result<Option<SystemTime>> {
+        let modified = fs::metadata(self.path)?.modified()?;
+        if modified == self.last {
+            return Ok(None);
+        }
+        self.last = modified;
+        Ok(Some(modified))
+    }
+}
+
+fn watch_modifications(
+    path: &Path,
+    poll: Duration,
+    max_polls: usize,
+) -> io::Result<Vec<SystemTime>> {
+    let mut watcher = ModificationWatcher::new(path)?;
+    let mut changes = Vec::new();
+    for _ in 0..max_polls {
+        thread::sleep(poll);
+        changes.extend(watcher.poll()?);
+    }
+    Ok(changes)
+}
+
+// Rewrites the file and gives it an explicit modification time, so the change is visible even
+// on file systems with a coarse grained clock.
+fn rewrite_at(path: &Path, content: &str, modified: SystemTime) -> io::Result<()> {
+    fs::write(path, content)?;
+    File::options()
+        .write(true)
+        .open(path)?
+        .set_times(FileTimes::new().set_modified(modified))
+}
+
+fn watcher_example(root: &Path) -> io::Result<()> {
+    let dir = root.join("watch");
+    fs::create_dir(&dir)?;
+    let path = dir.join("watched.txt");
+    fs::write(&path, "first version")?;
+    let untouched = watch_modifications(&path, Duration::from_millis(20), 3)?;
+
+    let mut watcher = ModificationWatcher::new(&path)?;
+    rewrite_at(&path, "second version", SystemTime::UNIX_EPOCH)?;
+    println!(
+        "watcher: {} changes while untouched, {:?} after a rewrite",
+        untouched.len(),
+        watcher.poll()?
+    );
+    Ok(())
+}
+
+// ARCHIVE PACKER
+//
+// A minimal tar-like format, every number is little endian:
+//
+// | magic "RLAR" | number of records: u32 | record | record | ...
+// record = | name length: u32 | name (UTF-8) | content length: u64 | content |
+//
+// The number of records is known only at the end, so pack() writes a placeholder, appends the
+// records and then seeks back to the start to write the real value.
+// Only the file name is stored, unpack() refuses names containing separators or "..", otherwise
+// a crafted archive could write outside of the destination folder.
+const ARCHIVE_MAGIC: &[u8; 4] = b"RLAR";
+
+fn pack(files: &[&Path], out: &Path) -> io::Result<()> {
+    let mut archive = BufWriter::new(File::create(out)?);
+    archive.write_all(ARCHIVE_MAGIC)?;
+    archive.write_all(&0u32.to_le_bytes())?;
+
+    let mut count: u32 = 0;
+    for path in files {
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "file name is not UTF-8"))?;
+        let mut content = File::open(path)?;
+        let length = content.metadata()?.len();
+        archive.write_all(&(name.len() as u32).to_le_bytes())?;
+        archive.write_all(name.as_bytes())?;
+        archive.write_all(&length.to_le_bytes())?;
+        let copied = io::copy(&mut content, &mut archive)?;
+        if copied != length {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "file changed while packing",
+            ));
+        }
+        count += 1;
+    }
+
+    archive.seek(SeekFrom::Start(ARCHIVE_MAGIC.len() as u64))?;
+    archive.write_all(&count.to_le_bytes())?;
+    archive.flush()
+}
+
+fn unpack(archive: &Path, dest: &Path) -> io::Result<Vec<PathBuf>> {
+    let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_string());
+    let mut archive = BufReader::new(File::open(archive)?);
+
+    let mut magic = [0; 4];
+    archive.read_exact(&mut magic)?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err(invalid("not an archive"));
+    }
+    let mut count = [0; 4];
+    archive.read_exact(&mut count)?;
+
+    let mut unpacked = Vec::new();
+    for _ in 0..u32::from_le_bytes(count) {
+        let mut length = [0; 4];
+        archive.read_exact(&mut length)?;
+        let mut name = vec![0; u32::from_le_bytes(length) as usize];
+        archive.read_exact(&mut name)?;
+        let name = String::from_utf8(name).map_err(|_| invalid("file name is not UTF-8"))?;
+        if name.is_empty() || name == ".." || name.contains(['/', '\\']) {
+            return Err(invalid("unsafe file name"));
+        }
+
+        let mut length = [0; 8];
+        archive.read_exact(&mut length)?;
+        let length = u64::from_le_bytes(length);
+        let path = dest.join(&name);
+        let mut file = File::create(&path)?;
+        if io::copy(&mut archive.by_ref().take(length), &mut file)? != length {
+            return Err(invalid("truncated archive"));
+        }
+        unpacked.push(path);
+    }
+    Ok(unpacked)
+}
+
+fn archive_example(root: &Path) -> io::Result<()> {
+    let dir = root.join("archive");
+    let extracted = dir.join("extracted");
+    fs::create_dir_all(&extracted)?;
+    let hello = dir.join("hello.txt");
+    fs::write(&hello, "hello archive\n")?;
+
+    let archive = dir.join("files.rlar");
+    pack(&[&hello], &archive)?;
+    let unpacked = unpack(&archive, &extracted)?;
+    println!(
+        "archive: unpacked {:?} from {} bytes",
+        unpacked[0].file_name().unwrap(),
+        fs::metadata(&archive)?.len()
+    );
+    Ok(())
+}
+
+// COMPRESSION (feature "flate2")
+//
+// GzEncoder wraps any Write and compresses what is written through it, GzDecoder wraps any Read
+// and decompresses what is read from it. Since both sides are just Read/Write, io::copy moves the
+// data between buffered file handles without loading the whole file in memory.
+// Both functions return the number of bytes written to `dst`.
+// Run with: cargo run --bin 9-input_output --features flate2
+#[cfg(feature = "flate2")]
+fn compress_file(src: &Path, dst: &Path) -> io::Result<u64> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut input = BufReader::new(File::open(src)?);
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(dst)?), Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    // finish() writes the gzip trailer, dropping the encoder would hide a failure.
+    encoder.finish()?.flush()?;
+    Ok(fs::metadata(dst)?.len())
+}
+
+#[cfg(feature = "flate2")]
+fn decompress_file(src: &Path, dst: &Path) -> io::Result<u64> {
+    use flate2::read::GzDecoder;
+
+    let mut decoder = GzDecoder::new(BufReader::new(File::open(src)?));
+    let mut output = BufWriter::new(File::create(dst)?);
+    let written = io::copy(&mut decoder, &mut output)?;
+    output.flush()?;
+    Ok(written)
+}
+
+// Writes `text` to dir/lines.txt, compresses it and decompresses the result again, returns the
+// size of the compressed file and the restored text.
+#[cfg(feature = "flate2")]
+fn gzip_round_trip(dir: &Path, text: &str) -> io::Result<(u64, String)> {
+    let original = dir.join("lines.txt");
+    let compressed = dir.join("lines.txt.gz");
+    let restored = dir.join("restored.txt");
+    fs::write(&original, text)?;
+    let compressed_size = compress_file(&original, &compressed)?;
+    decompress_file(&compressed, &restored)?;
+    Ok((compressed_size, fs::read_to_string(&restored)?))
+}
+
+#[cfg(feature = "flate2")]
+fn compression_example(root: &Path) -> io::Result<()> {
+    let dir = root.join("gzip");
+    fs::create_dir(&dir)?;
+    let text = "the same line over and over again\n".repeat(1000);
+    let (compressed_size, restored) = gzip_round_trip(&dir, &text)?;
+    println!(
+        "compression: {} bytes -> {} bytes -> {} bytes",
+        text.len(),
+        compressed_size,
+        restored.len()
+    );
+    Ok(())
+}
+
+// LINE INDEX
+//
+// Reading line N of a big log sequentially means reading all the N-1 lines before it. Scanning
+// the file once and remembering the byte offset where every line starts allows to jump directly
+// to any line later with seek().
+// A trailing newline at the end of the file doesn't start a new (empty) line.
+fn build_line_index(path: &Path) -> io::Result<Vec<u64>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut index = Vec::new();
+    let mut offset = 0;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            return Ok(index);
+        }
+        index.push(offset);
+        offset += read as u64;
+    }
+}
+
+fn read_line_at(path: &Path, index: &[u64], line: usize) -> io::Result<Option<String>> {
+    let Some(&offset) = index.get(line) else {
+        return Ok(None);
+    };
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut text = String::new();
+    BufReader::new(file).read_line(&mut text)?;
+    // Same terminators as BufRead::lines: "\r\n" or '\n'.
+    let len = text
+        .strip_suffix("\r\n")
+        .or_else(|| text.strip_suffix('\n'))
+        .unwrap_or(&text)
+        .len();
+    text.truncate(len);
+    Ok(Some(text))
+}
+
+fn line_index_example(root: &Path) -> io::Result<()> {
+    let dir = root.join("index");
+    fs::create_dir(&dir)?;
+    let path = dir.join("app.log");
+    let content = "INFO start\n\nWARN disk almost full\nERROR disk full\nINFO stop";
+    fs::write(&path, content)?;
+
+    let index = build_line_index(&path)?;
+    println!("line index: lines start at offsets {index:?}");
+    for line in [3, 0, 5] {
+        println!(
+            "line index: line {line} is {:?}",
+            read_line_at(&path, &index, line)?
+        );
+    }
+    Ok(())
+}
+
+fn run_examples(root: &Path) -> io::Result<()> {
+    glob_example(root)?;
+    watcher_example(root)?;
+    archive_example(root)?;
+    #[cfg(feature = "flate2")]
+    compression_example(root)?;
+    line_index_example(root)?;
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let root = std::env::temp_dir().join(format!("rust_learning_io_{}", std::process::id()));
+    if root.exists() {
+        fs::remove_dir_all(&root)?;
+    }
+    fs::create_dir_all(&root)?;
+    // The folder is removed also when an example fails.
+    let result = run_examples(&root);
+    fs::remove_dir_all(&root)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob() -> io::Result<()> {
+        assert!(glob_match("*.txt", "notes.txt"));
+        assert!(glob_match("*.txt", ".txt"));
+        assert!(!glob_match("*.txt", "notes.txt.bak"));
+        assert!(glob_match("file?.rs", "file1.rs"));
+        assert!(!glob_match("file?.rs", "file.rs"));
+        assert!(!glob_match("file?.rs", "file12.rs"));
+        assert!(glob_match("main.rs", "main.rs"));
+        assert!(!glob_match("main.rs", "main.rc"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXXbYYbZc"));
+        assert!(!glob_match("a*b*c", "aXXbYY"));
+
+        let dir = tempfile::tempdir()?;
+        for name in ["a.txt", "b.txt", "file1.rs", "file22.rs", "readme.md"] {
+            fs::write(dir.path().join(name), name)?;
+        }
+        let names = |paths: Vec<PathBuf>| -> Vec<String> {
+            paths
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(
+            names(list_matching(dir.path(), "*.txt")?),
+            vec!["a.txt", "b.txt"]
+        );
+        assert_eq!(
+            names(list_matching(dir.path(), "file?.rs")?),
+            vec!["file1.rs"]
+        );
+        assert_eq!(
+            names(list_matching(dir.path(), "readme.md")?),
+            vec!["readme.md"]
+        );
+        assert!(list_matching(dir.path(), "*.exe")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn watcher() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("watched.txt");
+        fs::write(&path, "first version")?;
+
+        // Nobody touches the file: no change is reported.
+        assert!(watch_modifications(&path, Duration::from_millis(10), 5)?.is_empty());
+
+        // The test drives the polls: every rewrite is complete, times included, before the
+        // watcher looks at the file.
+        let mut watcher = ModificationWatcher::new(&path)?;
+        assert_eq!(watcher.poll()?, None);
+        let times: Vec<SystemTime> = (0..2)
+            .map(|i| SystemTime::UNIX_EPOCH + Duration::from_secs(1000 + i))
+            .collect();
+        rewrite_at(&path, "second version", times[0])?;
+        assert_eq!(watcher.poll()?, Some(times[0]));
+        assert_eq!(watcher.poll()?, None);
+        rewrite_at(&path, "third version", times[1])?;
+        assert_eq!(watcher.poll()?, Some(times[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn archive() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let sources = dir.path().join("sources");
+        let extracted = dir.path().join("extracted");
+        fs::create_dir(&sources)?;
+        fs::create_dir(&extracted)?;
+
+        let contents: [(&str, Vec<u8>); 3] = [
+            ("hello.txt", b"hello archive\n".to_vec()),
+            ("empty.bin", Vec::new()),
+            ("bytes.bin", (0..=255).cycle().take(10_000).collect()),
+        ];
+        let mut paths = Vec::new();
+        for (name, content) in &contents {
+            let path = sources.join(name);
+            fs::write(&path, content)?;
+            paths.push(path);
+        }
+        let files: Vec<&Path> = paths.iter().map(|p| p.as_path()).collect();
+
+        let archive = dir.path().join("files.rlar");
+        pack(&files, &archive)?;
+        let unpacked = unpack(&archive, &extracted)?;
+        assert_eq!(unpacked.len(), contents.len());
+        for ((name, content), path) in contents.iter().zip(&unpacked) {
+            assert_eq!(path, &extracted.join(name));
+            assert_eq!(&fs::read(path)?, content);
+        }
+
+        // A file that isn't an archive is rejected.
+        assert_eq!(
+            unpack(&paths[0], &extracted).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn compression() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let text = "the same line over and over again\n".repeat(1000);
+        let (compressed_size, restored) = gzip_round_trip(dir.path(), &text)?;
+        assert!(compressed_size < text.len() as u64 / 10);
+        assert_eq!(restored, text);
+        Ok(())
+    }
+
+    #[test]
+    fn line_index() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("app.log");
+        let content = "INFO start\n\nWARN disk almost full\nERROR disk full\nINFO stop";
+        fs::write(&path, content)?;
+
+        let index = build_line_index(&path)?;
+        assert_eq!(index, vec![0, 11, 12, 34, 50]);
+
+        let sequential: Vec<String> = BufReader::new(File::open(&path)?)
+            .lines()
+            .collect::<io::Result<_>>()?;
+        for line in [3, 0, 4, 1, 2] {
+            assert_eq!(
+                read_line_at(&path, &index, line)?.as_ref(),
+                Some(&sequential[line])
+            );
+        }
+        assert_eq!(read_line_at(&path, &index, 5)?, None);
+
+        // With a trailing newline the number of lines doesn't change.
+        fs::write(&path, format!("{}\n", content))?;
+        assert_eq!(build_line_index(&path)?.len(), 5);
+        fs::write(&path, "")?;
+        assert!(build_line_index(&path)?.is_empty());
+
+        // Windows line endings: the '\r' isn't part of the line either.
+        fs::write(&path, "first\r\n\r\nthird\r\nlast")?;
+        let index = build_line_index(&path)?;
+        let lines: Vec<Option<String>> = (0..4)
+            .map(|line| read_line_at(&path, &index, line))
+            .collect::<io::Result<_>>()?;
+        assert_eq!(
+            lines,
+            ["first", "", "third", "last"].map(|l| Some(l.to_string()))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+// Collections built on top of the std ones: word counting with a HashMap, maps with any hasher,
+// a bit set, a sorted Vec and a union-find. The doc comments of the pub items carry doctests that cargo test compiles and
+// runs against the library.
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+/// Counts the occurrences of each word, words are split on whitespace and compared exactly.
+///
+/// ```
+/// let counts = esercizi_malnati::collections::word_count("the cat and the hat");
+/// assert_eq!(counts["the"], 2);
+/// assert_eq!(counts["cat"], 1);
+/// assert_eq!(counts.get("dog"), None);
+/// assert_eq!(counts.len(), 4);
+/// ```
+pub fn word_count(text: &str) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// The `n` most frequent words of `counts`, the most frequent first and equal counts in
+/// alphabetical order, so the result doesn't depend on the iteration order of the map.
+///
+/// ```
+/// use esercizi_malnati::collections::{most_frequent, word_count};
+///
+/// let counts = word_count("b a c b a b");
+/// assert_eq!(most_frequent(&counts, 2), [("b", 3), ("a", 2)]);
+/// # // Asking for more words than there are returns all of them.
+/// # assert_eq!(most_frequent(&counts, 10).len(), 3);
+/// ```
+pub fn most_frequent(counts: &HashMap<String, usize>, n: usize) -> Vec<(&str, usize)> {
+    let mut sorted: Vec<(&str, usize)> = counts.iter().map(|(w, &c)| (w.as_str(), c)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    sorted.truncate(n);
+    sorted
+}
+
+/// Maps every key to its position in `keys`, hashing with the `BuildHasher` `S`.
+///
+/// ```
+/// use esercizi_malnati::collections::build_map;
+/// use std::collections::hash_map::RandomState;
+///
+/// let keys = ["a".to_string(), "b".to_string()];
+/// let map = build_map::<RandomState>(&keys);
+/// assert_eq!(map["b"], 1);
+/// ```
+pub fn build_map<S: BuildHasher + Default>(keys: &[String]) -> HashMap<String, usize, S> {
+    let mut map = HashMap::with_hasher(S::default());
+    for (i, key) in keys.iter().enumerate() {
+        map.insert(key.clone(), i);
+    }
+    map
+}
+
+/// The value of every probe in `map`, `None` for the missing ones.
+///
+/// ```
+/// use esercizi_malnati::collections::{build_map, lookup_all};
+///
+/// let keys = ["a".to_string(), "b".to_string()];
+/// let map = build_map::<fnv::FnvBuildHasher>(&keys);
+/// assert_eq!(lookup_all(&map, &["b".to_string(), "c".to_string()]), [Some(1), None]);
+/// ```
+pub fn lookup_all<S: BuildHasher>(
+    map: &HashMap<String, usize, S>,
+    probes: &[String],
+) -> Vec<Option<usize>> {
+    probes.iter().map(|k| map.get(k).copied()).collect()
+}
+
+/// A set of small unsigned integers stored as the bits of a `Vec<u64>`: the element `i` is
+/// the bit `i % 64` of the word `i / 64`, and the words grow when a bigger element is set.
+/// The trailing zero words are dropped, so two sets with the same elements compare equal.
+///
+/// ```
+/// use esercizi_malnati::collections::BitSet;
+///
+/// let mut set = BitSet::new();
+/// set.set(3);
+/// set.set(200);
+/// assert!(set.contains(200) && !set.contains(4));
+/// assert_eq!(set.words().len(), 4);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet { words: Vec::new() }
+    }
+
+    pub fn set(&mut self, i: usize) {
+        let word = i / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (i % 64);
+    }
+
+    pub fn clear(&mut self, i: usize) {
+        if let Some(word) = self.words.get_mut(i / 64) {
+            *word &= !(1 << (i % 64));
+        }
+        self.trim();
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words
+            .get(i / 64)
+            .is_some_and(|word| word & (1 << (i % 64)) != 0)
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.words.truncate(other.words.len());
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// The elements in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.words.len() * 64).filter(|&i| self.contains(i))
+    }
+
+    /// The words holding the bits, the last one is never zero.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+/// A `Vec` kept always sorted, with binary search lookups. Duplicates are allowed.
+///
+/// ```
+/// use esercizi_malnati::collections::SortedVec;
+///
+/// let mut sorted = SortedVec::new();
+/// for value in [5, 1, 4, 1] {
+///     sorted.insert(value);
+/// }
+/// assert_eq!(sorted.as_slice(), [1, 1, 4, 5]);
+/// assert_eq!(sorted.remove(&1), Some(1));
+/// assert!(sorted.contains(&1) && !sorted.contains(&2));
+/// ```
+#[derive(Debug)]
+pub struct SortedVec<T: Ord> {
+    items: Vec<T>,
+}
+
+impl<T: Ord> Default for SortedVec<T> {
+    fn default() -> Self {
+        SortedVec::new()
+    }
+}
+
+impl<T: Ord> SortedVec<T> {
+    pub fn new() -> Self {
+        SortedVec { items: Vec::new() }
+    }
+
+    pub fn insert(&mut self, value: T) {
+        let index = match self.items.binary_search(&value) {
+            Ok(index) | Err(index) => index,
+        };
+        self.items.insert(index, value);
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.items.binary_search(value).is_ok()
+    }
+
+    /// Removes one element equal to `value`, if there is one.
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        let index = self.items.binary_search(value).ok()?;
+        Some(self.items.remove(index))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+}
+
+/// Disjoint sets of the elements `0..n`, every element starts alone in its own set and
+/// `union` merges two sets. Each set is a tree whose root identifies it.
+///
+/// ```
+/// use esercizi_malnati::collections::UnionFind;
+///
+/// let mut uf = UnionFind::new(5);
+/// uf.union(0, 1);
+/// uf.union(3, 1);
+/// assert!(uf.connected(0, 3));
+/// assert!(!uf.connected(0, 4));
+/// ```
+#[derive(Debug)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    /// The root of the set of `x`. Path halving: every visited node is linked to its
+    /// grandparent, so the next lookups are shorter.
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merges the sets of `a` and `b`. The smaller tree goes under the larger one, so the
+    /// trees stay shallow.
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
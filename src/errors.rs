@@ -0,0 +1,70 @@
+// An error type written by hand: Display gives its message and Error::source() the error that
+// caused it, so the whole chain can be printed. The doctests show both sides of the Result.
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+
+/// A line of text that isn't a valid number, the parse error is its source.
+#[derive(Debug)]
+pub struct LineError {
+    /// Number of the invalid line, counting from 1.
+    pub line: usize,
+    pub source: ParseIntError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid number at line {}", self.line)
+    }
+}
+
+impl Error for LineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Parses one integer per line, ignoring the spaces around it, and stops at the first line
+/// that isn't a number.
+///
+/// ```
+/// use esercizi_malnati::errors::parse_lines;
+///
+/// assert_eq!(parse_lines("1\n 2 \n-3").unwrap(), [1, 2, -3]);
+///
+/// let err = parse_lines("1\nx\ny").unwrap_err();
+/// assert_eq!(err.line, 2);
+/// assert_eq!(err.to_string(), "invalid number at line 2");
+/// ```
+pub fn parse_lines(text: &str) -> Result<Vec<i32>, LineError> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            line.trim().parse().map_err(|source| LineError {
+                line: i + 1,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// The messages of `err` and of all its causes, from the outermost to the root one.
+///
+/// ```
+/// use esercizi_malnati::errors::{error_chain, parse_lines};
+///
+/// let err = parse_lines("7\n\n").unwrap_err();
+/// assert_eq!(
+///     error_chain(&err),
+///     ["invalid number at line 2", "cannot parse integer from empty string"]
+/// );
+/// ```
+pub fn error_chain(err: &dyn Error) -> Vec<String> {
+    let mut chain = vec![err.to_string()];
+    let mut current = err.source();
+    while let Some(cause) = current {
+        chain.push(cause.to_string());
+        current = cause.source();
+    }
+    chain
+}
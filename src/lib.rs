@@ -0,0 +1,8 @@
+// The library crate of the package: the modules declared here can be used by every binary in
+// src/bin as esercizi_malnati::module and by the integration tests in the tests folder.
+// The examples in their doc comments are run as doctests: cargo test --doc
+pub mod collections;
+pub mod errors;
+#[cfg(feature = "parking_lot")]
+pub mod rwlock_cache;
+pub mod sorting;
@@ -0,0 +1,82 @@
+// The read-heavy cache of 12-concurrency.rs (a HashMap behind a RwLock, 99 reads every write)
+// built on std::sync::RwLock and on parking_lot::RwLock. It lives in the library so that the
+// binary and the criterion benchmark in benches/rwlock_cache.rs run the same code.
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::thread;
+
+/// Number of keys in a prefilled cache.
+pub const CACHE_KEYS: u64 = 1000;
+
+/// A map from keys to values shared by the threads of the workload.
+pub trait SharedCache: Sync {
+    fn get(&self, key: u64) -> Option<u64>;
+    fn insert(&self, key: u64, value: u64);
+}
+
+/// The cache behind a std::sync::RwLock, its guards come in a Result because of poisoning.
+pub struct StdCache(pub RwLock<HashMap<u64, u64>>);
+
+impl SharedCache for StdCache {
+    fn get(&self, key: u64) -> Option<u64> {
+        self.0.read().unwrap().get(&key).copied()
+    }
+
+    fn insert(&self, key: u64, value: u64) {
+        self.0.write().unwrap().insert(key, value);
+    }
+}
+
+/// The cache behind a parking_lot::RwLock, which has no poisoning and is eventually fair.
+pub struct ParkingLotCache(pub parking_lot::RwLock<HashMap<u64, u64>>);
+
+impl SharedCache for ParkingLotCache {
+    fn get(&self, key: u64) -> Option<u64> {
+        self.0.read().get(&key).copied()
+    }
+
+    fn insert(&self, key: u64, value: u64) {
+        self.0.write().insert(key, value);
+    }
+}
+
+/// Maps every key below [`CACHE_KEYS`] to its square.
+pub fn prefilled() -> HashMap<u64, u64> {
+    (0..CACHE_KEYS).map(|k| (k, k * k)).collect()
+}
+
+/// Runs `ops` operations on each of `threads` threads, one write every 100 reads, and returns
+/// the sum of the values read. Every write stores the value already there, so the sum doesn't
+/// depend on the scheduling nor on the lock.
+///
+/// ```
+/// use esercizi_malnati::rwlock_cache::{prefilled, read_heavy_workload, ParkingLotCache, StdCache};
+///
+/// let std_cache = StdCache(std::sync::RwLock::new(prefilled()));
+/// let parking_cache = ParkingLotCache(parking_lot::RwLock::new(prefilled()));
+/// assert_eq!(
+///     read_heavy_workload(&std_cache, 4, 1000),
+///     read_heavy_workload(&parking_cache, 4, 1000)
+/// );
+/// ```
+pub fn read_heavy_workload(cache: &impl SharedCache, threads: u64, ops: u64) -> u64 {
+    thread::scope(|s| {
+        let workers: Vec<_> = (0..threads)
+            .map(|t| {
+                s.spawn(move || {
+                    let mut checksum = 0;
+                    for i in 0..ops {
+                        let key = (t * ops + i) % CACHE_KEYS;
+                        if i % 100 == 0 {
+                            cache.insert(key, key * key);
+                        } else {
+                            checksum += cache.get(key).unwrap();
+                        }
+                    }
+                    checksum
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).sum()
+    })
+}
@@ -0,0 +1,86 @@
+// Three modules each sorting in a different way: in-order visit of a binary search tree,
+// draining a BinaryHeap and quicksort. They are used by the 11-module_test binary and checked
+// against Vec::sort by the integration tests in tests/sorting.rs, which see only the pub items.
+pub mod bst {
+    struct Node {
+        value: i32,
+        left: Option<Box<Node>>,
+        right: Option<Box<Node>>,
+    }
+
+    fn insert(link: &mut Option<Box<Node>>, value: i32) {
+        let mut link = link;
+        while let Some(node) = link {
+            link = if value < node.value {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+        *link = Some(Box::new(Node {
+            value,
+            left: None,
+            right: None,
+        }));
+    }
+
+    fn in_order(link: &Option<Box<Node>>, out: &mut Vec<i32>) {
+        if let Some(node) = link {
+            in_order(&node.left, out);
+            out.push(node.value);
+            in_order(&node.right, out);
+        }
+    }
+
+    pub fn sort(values: &[i32]) -> Vec<i32> {
+        let mut root = None;
+        for &v in values {
+            insert(&mut root, v);
+        }
+        let mut out = Vec::with_capacity(values.len());
+        in_order(&root, &mut out);
+        out
+    }
+}
+
+pub mod heap {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    pub fn sort(values: &[i32]) -> Vec<i32> {
+        let mut heap: BinaryHeap<Reverse<i32>> = values.iter().copied().map(Reverse).collect();
+        let mut out = Vec::with_capacity(values.len());
+        while let Some(Reverse(v)) = heap.pop() {
+            out.push(v);
+        }
+        out
+    }
+}
+
+pub mod quick {
+    pub fn sort(values: &[i32]) -> Vec<i32> {
+        let mut out = values.to_vec();
+        quicksort(&mut out);
+        out
+    }
+
+    fn quicksort(v: &mut [i32]) {
+        if v.len() <= 1 {
+            return;
+        }
+        // Lomuto partition with the middle element as pivot.
+        let last = v.len() - 1;
+        v.swap(v.len() / 2, last);
+        let mut store = 0;
+        for i in 0..last {
+            if v[i] < v[last] {
+                v.swap(i, store);
+                store += 1;
+            }
+        }
+        v.swap(store, last);
+        let (left, right) = v.split_at_mut(store);
+        quicksort(left);
+        quicksort(&mut right[1..]);
+    }
+}
@@ -0,0 +1,68 @@
+// Integration tests of the sorting modules: this file is compiled as a separate crate that
+// uses the library like any other dependency, so only its public API is visible.
+// The inputs come from proptest with a fixed seed: every run checks the same cases, so a
+// failure can be reproduced, and proptest shrinks a failing input before reporting it.
+use esercizi_malnati::sorting::{bst, heap, quick};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+
+type Sorter = fn(&[i32]) -> Vec<i32>;
+
+const SORTERS: [(&str, Sorter); 3] = [
+    ("bst", bst::sort),
+    ("heap", heap::sort),
+    ("quick", quick::sort),
+];
+
+fn seeded_runner() -> TestRunner {
+    let config = Config {
+        cases: 200,
+        failure_persistence: None,
+        ..Config::default()
+    };
+    TestRunner::new_with_rng(
+        config,
+        TestRng::from_seed(RngAlgorithm::ChaCha, &[0x5e; 32]),
+    )
+}
+
+fn agree_with_vec_sort(inputs: impl Strategy<Value = Vec<i32>>) {
+    let result = seeded_runner().run(&inputs, |input| {
+        let mut expected = input.clone();
+        expected.sort();
+        for (name, sort) in SORTERS {
+            prop_assert_eq!(sort(&input), expected.clone(), "{} diverged", name);
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        panic!("{e}");
+    }
+}
+
+#[test]
+fn many_duplicates() {
+    agree_with_vec_sort(vec(-5..5, 0..64));
+}
+
+#[test]
+fn mostly_distinct() {
+    agree_with_vec_sort(vec(-500_000..500_000, 0..64));
+}
+
+#[test]
+fn whole_range() {
+    agree_with_vec_sort(vec(any::<i32>(), 0..64));
+}
+
+#[test]
+fn already_ordered() {
+    let sorted: Vec<i32> = (0..50).collect();
+    let reversed: Vec<i32> = sorted.iter().rev().copied().collect();
+    for (name, sort) in SORTERS {
+        assert_eq!(sort(&sorted), sorted, "{name} on sorted input");
+        assert_eq!(sort(&reversed), sorted, "{name} on reversed input");
+        assert_eq!(sort(&[]), Vec::<i32>::new(), "{name} on empty input");
+    }
+}